@@ -0,0 +1,44 @@
+use std::sync::Arc;
+
+use raytracing_in_one_weekend::camera::Camera;
+use raytracing_in_one_weekend::hittable_list::HittableList;
+use raytracing_in_one_weekend::material::Material::Lambertian;
+use raytracing_in_one_weekend::sphere::Sphere;
+use raytracing_in_one_weekend::texture::{CheckerTexture, SolidColor, Texture};
+use raytracing_in_one_weekend::vec3::{Color, Point3, Vec3};
+
+/// Two large spheres sharing a solid checker texture, showing how it carves space into cubes
+fn main() {
+    let checker: Arc<dyn Texture> = Arc::new(CheckerTexture::new(
+        0.32,
+        Arc::new(SolidColor::new(Color::new(0.2, 0.3, 0.1))),
+        Arc::new(SolidColor::new(Color::new(0.9, 0.9, 0.9))),
+    ));
+
+    let mut world = HittableList::default();
+    world.add(Box::new(Sphere::new(
+        Point3::new(0., -10., 0.),
+        10.,
+        Lambertian {
+            albedo: Arc::clone(&checker),
+        },
+    )));
+    world.add(Box::new(Sphere::new(
+        Point3::new(0., 10., 0.),
+        10.,
+        Lambertian { albedo: checker },
+    )));
+
+    let camera = Camera::builder()
+        .aspect_ratio(16. / 9.)
+        .image_width(400)
+        .samples_per_pixel(100)
+        .max_depth(50)
+        .vfov(20.)
+        .look_from(Point3::new(13., 2., 3.))
+        .look_at(Point3::new(0., 0., 0.))
+        .vup(Vec3::new(0., 1., 0.))
+        .build();
+
+    camera.render(&world, &HittableList::default());
+}
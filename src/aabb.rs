@@ -0,0 +1,195 @@
+use crate::interval::Interval;
+use crate::ray::Ray;
+use crate::vec3::Point3;
+
+/// Axis-aligned bounding box, stored as one interval per axis
+#[derive(Clone)]
+pub struct Aabb {
+    x: Interval,
+    y: Interval,
+    z: Interval,
+}
+
+impl Aabb {
+    #[must_use]
+    pub fn new(x: Interval, y: Interval, z: Interval) -> Self {
+        let mut bbox = Self { x, y, z };
+        bbox.pad_to_minimums();
+        bbox
+    }
+
+    /// The empty box, which contains no points and is the identity for `union`
+    #[must_use]
+    pub const fn empty() -> Self {
+        Self {
+            x: Interval::empty(),
+            y: Interval::empty(),
+            z: Interval::empty(),
+        }
+    }
+
+    /// The box containing all of space, for unbounded objects
+    #[must_use]
+    pub const fn universe() -> Self {
+        Self {
+            x: Interval::universe(),
+            y: Interval::universe(),
+            z: Interval::universe(),
+        }
+    }
+
+    /// Treat the two points `a` and `b` as extrema for the bounding box, so
+    /// we don't require a particular minimum/maximum coordinate order
+    #[must_use]
+    pub fn from_points(a: &Point3, b: &Point3) -> Self {
+        Self::new(
+            Interval::new(a.x().min(b.x()), a.x().max(b.x())),
+            Interval::new(a.y().min(b.y()), a.y().max(b.y())),
+            Interval::new(a.z().min(b.z()), a.z().max(b.z())),
+        )
+    }
+
+    /// Returns the tightest box enclosing both `a` and `b`
+    #[must_use]
+    pub const fn union(a: &Self, b: &Self) -> Self {
+        Self {
+            x: Interval::union(&a.x, &b.x),
+            y: Interval::union(&a.y, &b.y),
+            z: Interval::union(&a.z, &b.z),
+        }
+    }
+
+    #[must_use]
+    pub const fn axis_interval(&self, n: usize) -> &Interval {
+        match n {
+            1 => &self.y,
+            2 => &self.z,
+            _ => &self.x,
+        }
+    }
+
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        (0..3).any(|axis| self.axis_interval(axis).size() < 0.)
+    }
+
+    /// Returns true if the two boxes share at least one point
+    #[must_use]
+    pub fn overlaps(&self, other: &Self) -> bool {
+        (0..3).all(|axis| self.axis_interval(axis).overlaps(other.axis_interval(axis)))
+    }
+
+    /// Returns true if `p` lies inside the box or on its boundary
+    #[must_use]
+    pub fn contains(&self, p: &Point3) -> bool {
+        (0..3).all(|axis| self.axis_interval(axis).contains(p[axis]))
+    }
+
+    /// Returns the midpoint of the box
+    #[must_use]
+    pub fn centroid(&self) -> Point3 {
+        Point3::new(
+            0.5 * (self.x.min() + self.x.max()),
+            0.5 * (self.y.min() + self.y.max()),
+            0.5 * (self.z.min() + self.z.max()),
+        )
+    }
+
+    /// Returns the total area of the six faces of the box, or zero if it is empty
+    #[must_use]
+    pub fn surface_area(&self) -> f64 {
+        if self.is_empty() {
+            return 0.;
+        }
+
+        let (dx, dy, dz) = (self.x.size(), self.y.size(), self.z.size());
+        2. * dx.mul_add(dy, dy.mul_add(dz, dz * dx))
+    }
+
+    /// Adjust the box so that no side is narrower than some delta, padding if necessary.
+    /// Flat objects would otherwise get zero-width boxes that rays can slip past.
+    fn pad_to_minimums(&mut self) {
+        let delta = 0.0001;
+        for axis in [&mut self.x, &mut self.y, &mut self.z] {
+            if axis.size() >= 0. && axis.size() < delta {
+                *axis = axis.expand(delta);
+            }
+        }
+    }
+
+    /// Slab test: returns true if the ray passes through the box within `ray_t`
+    #[must_use]
+    pub fn hit(&self, ray: &Ray, ray_t: &Interval) -> bool {
+        self.hit_interval(ray, ray_t).is_some()
+    }
+
+    /// Slab test: returns the part of `ray_t` during which the ray is inside the box
+    #[must_use]
+    pub fn hit_interval(&self, ray: &Ray, ray_t: &Interval) -> Option<Interval> {
+        // The slabs of an empty box are inverted, which the test below doesn't catch
+        if self.is_empty() {
+            return None;
+        }
+
+        let ray_orig = ray.origin();
+        let ray_dir = ray.direction();
+
+        let mut t_min = ray_t.min();
+        let mut t_max = ray_t.max();
+
+        for axis in 0..3 {
+            let ax = self.axis_interval(axis);
+            let ad_inv = 1. / ray_dir[axis];
+
+            let t0 = (ax.min() - ray_orig[axis]) * ad_inv;
+            let t1 = (ax.max() - ray_orig[axis]) * ad_inv;
+
+            let (t0, t1) = if t0 < t1 { (t0, t1) } else { (t1, t0) };
+            if t0 > t_min {
+                t_min = t0;
+            }
+            if t1 < t_max {
+                t_max = t1;
+            }
+
+            if t_max <= t_min {
+                return None;
+            }
+        }
+
+        Some(Interval::new(t_min, t_max))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::vec3::Vec3;
+
+    #[test]
+    fn empty_box_is_never_hit() {
+        let ray_t = Interval::new(0., f64::INFINITY);
+        for direction in [
+            Vec3::new(1., 0., 0.),
+            Vec3::new(-1., -1., -1.),
+            Vec3::new(0.3, -0.2, 0.9),
+        ] {
+            let ray = Ray::new(Point3::new(0., 0., 0.), direction, 0.);
+            assert!(!Aabb::empty().hit(&ray, &ray_t));
+            assert!(Aabb::empty().hit_interval(&ray, &ray_t).is_none());
+        }
+    }
+
+    #[test]
+    fn hit_interval_spans_the_box() {
+        let bbox = Aabb::from_points(&Point3::new(1., -1., -1.), &Point3::new(3., 1., 1.));
+        let ray = Ray::new(Point3::new(0., 0., 0.), Vec3::new(1., 0., 0.), 0.);
+        let inside = bbox
+            .hit_interval(&ray, &Interval::new(0., f64::INFINITY))
+            .expect("ray along the x axis passes through the box");
+        assert!((inside.min() - 1.).abs() < 1e-12 && (inside.max() - 3.).abs() < 1e-12);
+
+        let away = Ray::new(Point3::new(0., 0., 0.), Vec3::new(-1., 0., 0.), 0.);
+        assert!(!bbox.hit(&away, &Interval::new(0., f64::INFINITY)));
+    }
+}
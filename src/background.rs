@@ -0,0 +1,80 @@
+use crate::vec3::{Color, Vec3};
+
+/// Light arriving from infinitely far away, seen by rays that escape the
+/// scene. A gradient or a solid color can be given as a closure of the
+/// direction.
+pub trait Background: Send + Sync {
+    /// Radiance arriving from the unit `direction`
+    fn color(&self, direction: &Vec3) -> Color;
+
+    /// Whether `sample` can pick directions, so that the background is worth
+    /// sampling as a light. Must be overridden along with `sample` and `pdf`.
+    fn is_samplable(&self) -> bool {
+        false
+    }
+
+    /// Picks a unit direction to gather light from, favoring bright parts of the
+    /// background, along with its probability density over solid angle.
+    /// Returns `None` for backgrounds that cannot be importance sampled.
+    fn sample(&self) -> Option<(Vec3, f64)> {
+        None
+    }
+
+    /// Probability density over solid angle with which `sample` picks the unit `direction`
+    fn pdf(&self, _direction: &Vec3) -> f64 {
+        0.
+    }
+}
+
+impl<F> Background for F
+where
+    F: Fn(&Vec3) -> Color + Send + Sync,
+{
+    fn color(&self, direction: &Vec3) -> Color {
+        self(direction)
+    }
+}
+
+/// The same color in every direction. Black gives a scene lit only by its lights.
+pub struct SolidBackground {
+    color: Color,
+}
+
+impl SolidBackground {
+    #[must_use]
+    pub const fn new(color: Color) -> Self {
+        Self { color }
+    }
+}
+
+impl Background for SolidBackground {
+    fn color(&self, _direction: &Vec3) -> Color {
+        self.color.clone()
+    }
+}
+
+/// Vertical blend from `bottom`, looking straight down, to `top`, looking straight up
+pub struct GradientBackground {
+    bottom: Color,
+    top: Color,
+}
+
+impl GradientBackground {
+    #[must_use]
+    pub const fn new(bottom: Color, top: Color) -> Self {
+        Self { bottom, top }
+    }
+
+    /// White fading into light blue overhead
+    #[must_use]
+    pub const fn sky() -> Self {
+        Self::new(Color::new(1., 1., 1.), Color::new(0.5, 0.7, 1.0))
+    }
+}
+
+impl Background for GradientBackground {
+    fn color(&self, direction: &Vec3) -> Color {
+        let a = 0.5 * (direction.y() + 1.0);
+        (1. - a) * &self.bottom + a * &self.top
+    }
+}
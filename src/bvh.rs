@@ -0,0 +1,356 @@
+use rand::Rng;
+
+use crate::aabb::Aabb;
+use crate::hittable::{HitRecord, HitTrace, Hittable};
+use crate::hittable_list::HittableList;
+use crate::interval::Interval;
+use crate::ray::Ray;
+use crate::vec3::{Point3, Vec3};
+
+/// Estimated cost of visiting a node relative to intersecting one primitive
+const TRAVERSAL_COST: f64 = 0.125;
+
+/// Largest number of primitives a leaf may hold before it must be split
+const MAX_LEAF_SIZE: usize = 4;
+
+/// Object to build a hierarchy over, with its position in the original list
+type IndexedObject = (usize, Box<dyn Hittable>);
+
+/// Bounding volume hierarchy over a set of hittables, split with the
+/// surface area heuristic (SAH)
+pub struct BvhNode {
+    bbox: Aabb,
+    len: usize, // Number of objects below this node
+    node: Node,
+}
+
+enum Node {
+    Leaf(HittableList),
+    Branch {
+        left: Box<BvhNode>,
+        right: Box<BvhNode>,
+    },
+}
+
+impl BvhNode {
+    #[must_use]
+    pub fn new(list: HittableList) -> Self {
+        Self::build(list.into_indexed_objects())
+    }
+
+    /// Returns the objects whose bounding boxes overlap `region`, skipping
+    /// any subtree whose box lies entirely outside it
+    #[must_use]
+    pub fn overlapping(&self, region: &Aabb) -> Vec<&dyn Hittable> {
+        let mut found = Vec::new();
+        self.collect_overlapping(region, &mut found);
+        found
+    }
+
+    fn collect_overlapping<'a>(&'a self, region: &Aabb, found: &mut Vec<&'a dyn Hittable>) {
+        if !self.bbox.overlaps(region) {
+            return;
+        }
+
+        match &self.node {
+            Node::Leaf(objects) => found.extend(objects.overlapping(region)),
+            Node::Branch { left, right } => {
+                left.collect_overlapping(region, found);
+                right.collect_overlapping(region, found);
+            }
+        }
+    }
+
+    #[allow(clippy::cast_precision_loss)]
+    fn build(mut objects: Vec<IndexedObject>) -> Self {
+        let bbox = objects.iter().fold(Aabb::empty(), |acc, (_, obj)| {
+            Aabb::union(&acc, obj.bounding_box())
+        });
+
+        let count = objects.len();
+        let split = if count > 1 {
+            Self::best_split(&mut objects, &bbox)
+        } else {
+            None
+        };
+
+        // Keep the primitives together if splitting them isn't expected to pay off
+        let (axis, index) = match split {
+            Some((axis, index, cost)) if count > MAX_LEAF_SIZE || cost < count as f64 => {
+                (axis, index)
+            }
+            _ => {
+                let mut leaf = HittableList::default();
+                for (index, object) in objects {
+                    leaf.add_indexed(index, object);
+                }
+                return Self {
+                    bbox,
+                    len: count,
+                    node: Node::Leaf(leaf),
+                };
+            }
+        };
+
+        sort_by_centroid(&mut objects, axis);
+        let right = objects.split_off(index);
+        let left = objects;
+
+        Self {
+            bbox,
+            len: count,
+            node: Node::Branch {
+                left: Box::new(Self::build(left)),
+                right: Box::new(Self::build(right)),
+            },
+        }
+    }
+
+    /// Finds the axis and partition index with the lowest SAH cost, returned
+    /// alongside that cost in units of primitive intersections
+    #[allow(clippy::cast_precision_loss)]
+    fn best_split(objects: &mut [IndexedObject], bbox: &Aabb) -> Option<(usize, usize, f64)> {
+        let count = objects.len();
+        let parent_area = bbox.surface_area();
+        if !(parent_area > 0. && parent_area.is_finite()) {
+            // Degenerate and unbounded boxes carry no usable area information, so
+            // fall back to a median split
+            return Some((0, count / 2, count as f64));
+        }
+
+        let mut best: Option<(usize, usize, f64)> = None;
+
+        // Right-hand areas for every split position, reused for each axis
+        let mut right_areas = vec![0.; count];
+
+        for axis in 0..3 {
+            sort_by_centroid(objects, axis);
+
+            let mut right_box = Aabb::empty();
+            for i in (1..count).rev() {
+                right_box = Aabb::union(&right_box, objects[i].1.bounding_box());
+                right_areas[i] = right_box.surface_area();
+            }
+
+            let mut left_box = Aabb::empty();
+            for i in 1..count {
+                left_box = Aabb::union(&left_box, objects[i - 1].1.bounding_box());
+
+                let weighted = left_box
+                    .surface_area()
+                    .mul_add(i as f64, right_areas[i] * (count - i) as f64);
+                let cost = TRAVERSAL_COST + weighted / parent_area;
+
+                if best
+                    .as_ref()
+                    .is_none_or(|(_, _, best_cost)| cost < *best_cost)
+                {
+                    best = Some((axis, i, cost));
+                }
+            }
+        }
+
+        best
+    }
+}
+
+fn sort_by_centroid(objects: &mut [IndexedObject], axis: usize) {
+    objects.sort_by(|(_, a), (_, b)| {
+        let a_center = a.bounding_box().centroid()[axis];
+        let b_center = b.bounding_box().centroid()[axis];
+        a_center.total_cmp(&b_center)
+    });
+}
+
+impl Hittable for BvhNode {
+    fn hit(&self, ray: &Ray, ray_t: &Interval) -> Option<HitRecord> {
+        if !self.bbox.hit(ray, ray_t) {
+            return None;
+        }
+
+        match &self.node {
+            Node::Leaf(objects) => objects.hit(ray, ray_t),
+            Node::Branch { left, right } => {
+                let hit_left = left.hit(ray, ray_t);
+                let closest_so_far = hit_left.as_ref().map_or_else(|| ray_t.max(), HitRecord::t);
+                let hit_right = right.hit(ray, &Interval::new(ray_t.min(), closest_so_far));
+
+                hit_right.or(hit_left)
+            }
+        }
+    }
+
+    fn bounding_box(&self) -> &Aabb {
+        &self.bbox
+    }
+
+    /// Same traversal as `hit`, counting every node whose box is tested
+    fn hit_traced(&self, ray: &Ray, ray_t: &Interval, trace: &mut HitTrace) -> Option<HitRecord> {
+        trace.node_visits += 1;
+        if !self.bbox.hit(ray, ray_t) {
+            return None;
+        }
+
+        match &self.node {
+            Node::Leaf(objects) => objects.hit_traced(ray, ray_t, trace),
+            Node::Branch { left, right } => {
+                let hit_left = left.hit_traced(ray, ray_t, trace);
+                let closest_so_far = hit_left.as_ref().map_or_else(|| ray_t.max(), HitRecord::t);
+                let hit_right =
+                    right.hit_traced(ray, &Interval::new(ray_t.min(), closest_so_far), trace);
+
+                hit_right.or(hit_left)
+            }
+        }
+    }
+
+    fn occluded(&self, ray: &Ray, ray_t: &Interval) -> bool {
+        if !self.bbox.hit(ray, ray_t) {
+            return false;
+        }
+
+        match &self.node {
+            Node::Leaf(objects) => objects.occluded(ray, ray_t),
+            Node::Branch { left, right } => left.occluded(ray, ray_t) || right.occluded(ray, ray_t),
+        }
+    }
+
+    /// Picks one of the objects evenly, like `HittableList::sample`, by
+    /// descending into each child in proportion to how many objects it holds
+    fn sample(&self, origin: &Point3, time: f64) -> Option<(Vec3, f64)> {
+        let mut node = self;
+        let leaf = loop {
+            match &node.node {
+                Node::Leaf(objects) => break objects,
+                Node::Branch { left, right } => {
+                    node = if rand::thread_rng().gen_range(0..node.len) < left.len {
+                        left
+                    } else {
+                        right
+                    };
+                }
+            }
+        };
+        let (direction, _) = leaf.sample(origin, time)?;
+        let pdf = self.pdf(origin, &direction, time);
+        Some((direction, pdf))
+    }
+
+    /// Average density over all the objects, as for `HittableList::pdf`
+    #[allow(clippy::cast_precision_loss)]
+    fn pdf(&self, origin: &Point3, direction: &Vec3, time: f64) -> f64 {
+        match &self.node {
+            Node::Leaf(objects) => objects.pdf(origin, direction, time),
+            Node::Branch { left, right } => {
+                let total = (left.len as f64).mul_add(
+                    left.pdf(origin, direction, time),
+                    right.len as f64 * right.pdf(origin, direction, time),
+                );
+                total / self.len as f64
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
+
+    use super::*;
+    use crate::material::Material;
+    use crate::quad::Quad;
+    use crate::sphere::Sphere;
+    use crate::texture::SolidColor;
+    use crate::vec3::{Color, Point3, Vec3};
+
+    /// The same scene of spheres and flat quads each time, for a given seed
+    fn scene(seed: u64) -> HittableList {
+        let mut rng = StdRng::seed_from_u64(seed);
+        let mut point = |scale: f64| {
+            Point3::new(
+                rng.gen_range(-scale..scale),
+                rng.gen_range(-scale..scale),
+                rng.gen_range(-scale..scale),
+            )
+        };
+        let mat = Material::Lambertian {
+            albedo: Arc::new(SolidColor::new(Color::new(0.5, 0.5, 0.5))),
+        };
+
+        let mut list = HittableList::default();
+        for _ in 0..200 {
+            list.add(Box::new(Sphere::new(point(20.), 1., mat.clone())));
+        }
+        // Axis-aligned quads have flat boxes, which rely on the box padding
+        for _ in 0..50 {
+            list.add(Box::new(Quad::new(
+                point(20.),
+                Vec3::new(2., 0., 0.),
+                Vec3::new(0., 0., 1.),
+                mat.clone(),
+            )));
+        }
+        list
+    }
+
+    #[test]
+    fn matches_flat_list() {
+        let list = scene(7);
+        let bvh = BvhNode::new(scene(7));
+
+        let mut rng = StdRng::seed_from_u64(11);
+        let ray_t = Interval::new(0.001, f64::INFINITY);
+        let mut hits = 0;
+        for _ in 0..5_000 {
+            let origin = Point3::new(
+                rng.gen_range(-25. ..25.),
+                rng.gen_range(-25. ..25.),
+                rng.gen_range(-25. ..25.),
+            );
+            let ray = Ray::new(origin, Vec3::random_unit_vector(), 0.);
+
+            let expected = list.hit(&ray, &ray_t).map(|hit_record| hit_record.t());
+            let found = bvh.hit(&ray, &ray_t).map(|hit_record| hit_record.t());
+            match (expected, found) {
+                (Some(expected), Some(found)) => {
+                    assert!((expected - found).abs() < 1e-9, "{expected} != {found}");
+                    hits += 1;
+                }
+                (None, None) => {}
+                _ => panic!("list found {expected:?}, BVH found {found:?}"),
+            }
+            assert_eq!(list.occluded(&ray, &ray_t), bvh.occluded(&ray, &ray_t));
+        }
+        // Make sure the rays actually exercise the hierarchy
+        assert!(hits > 500, "only {hits} rays hit anything");
+    }
+
+    #[test]
+    fn samples_like_flat_list() {
+        let list = scene(7);
+        let bvh = BvhNode::new(scene(7));
+
+        let mut rng = StdRng::seed_from_u64(13);
+        let mut sampled = 0;
+        for _ in 0..500 {
+            let origin = Point3::new(
+                rng.gen_range(-25. ..25.),
+                rng.gen_range(-25. ..25.),
+                rng.gen_range(-25. ..25.),
+            );
+            let Some((direction, pdf)) = bvh.sample(&origin, 0.) else {
+                continue;
+            };
+            let expected = list.pdf(&origin, &direction, 0.);
+            assert!(
+                (pdf - expected).abs() <= 1e-9 * expected,
+                "BVH density {pdf}, list density {expected}"
+            );
+            sampled += 1;
+        }
+        assert!(sampled > 250, "only {sampled} samples succeeded");
+    }
+}
@@ -1,21 +1,34 @@
-use image::{ImageBuffer, RgbImage};
+use std::iter;
+use std::path::{Path, PathBuf};
+
+use image::{ImageBuffer, ImageFormat, ImageResult, Rgb, Rgb32FImage, RgbImage};
 use indicatif::ParallelProgressIterator;
 use rand::Rng;
 use rayon::prelude::*;
 
+use crate::background::{Background, GradientBackground};
+use crate::color::to_byte;
+use crate::debug::DebugMode;
 use crate::hittable::Hittable;
 use crate::hittable_list::HittableList;
-use crate::interval::Interval;
+use crate::integrator::{Integrator, PathTracer, Scene};
 use crate::ray::Ray;
 use crate::vec3::{Color, Point3, Vec3};
 
 pub struct Camera {
     image_width: u32,       // Rendered image width in pixel count
     samples_per_pixel: u32, // Count of random samples for each pixel
-    max_depth: u32,         // Maximum number of ray bounces in scene
 
     defocus_angle: f64, // Variation angle of rays through each pixel
 
+    shutter_open: f64,  // Time at which the exposure starts
+    shutter_close: f64, // Time at which the exposure ends
+
+    background: Box<dyn Background>, // Color of rays that escape the scene
+    integrator: Box<dyn Integrator>, // Light transport algorithm run for each sample
+    output: PathBuf,                 // Where the rendered image is saved
+    passes: Vec<(PathBuf, Box<dyn Integrator>)>, // Extra images rendered alongside
+
     image_height: u32,        // Rendered image height
     pixel_samples_scale: f64, // Color scale factor for a sum of pixel samples
     center: Point3,           // Camera center
@@ -27,56 +40,89 @@ pub struct Camera {
 }
 
 impl Camera {
+    #[must_use]
     pub const fn builder() -> CamBuilder {
         CamBuilder::default()
     }
 
-    pub fn render(&self, world: &HittableList) {
-        let mut buffer: RgbImage = ImageBuffer::new(self.image_width, self.image_height);
-        let length = buffer.len() as u64;
-        buffer
-            .par_enumerate_pixels_mut()
+    /// Render `world` and save the result to the output image, `image.png`
+    /// unless set otherwise. Diffuse surfaces are lit directly by sampling
+    /// `lights`, which should hold the emissive objects of the world, as well
+    /// as by the background where it can be sampled. These can be instances or
+    /// hierarchies too, sharing their geometry with the world.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the rendered image cannot be saved.
+    pub fn render(&self, world: &dyn Hittable, lights: &HittableList) {
+        let scene = Scene::new(world, lights, self.background.as_ref());
+
+        // The main image comes first, followed by any extra passes
+        let outputs: Vec<(&Path, &dyn Integrator)> =
+            iter::once((self.output.as_path(), self.integrator.as_ref()))
+                .chain(
+                    self.passes
+                        .iter()
+                        .map(|(path, integrator)| (path.as_path(), integrator.as_ref())),
+                )
+                .collect();
+
+        let length = u64::from(self.image_width) * u64::from(self.image_height);
+        let pixels: Vec<Vec<Color>> = (0..self.image_height)
             .into_par_iter()
+            .flat_map_iter(|y| (0..self.image_width).map(move |x| (x, y)))
             .progress_count(length)
-            .for_each(|(x, y, pixel)| {
-                let mut pixel_color = Color::new(0., 0., 0.);
+            .map(|(x, y)| {
+                // Every output sees the same camera rays
+                let mut pixel_colors = vec![Color::new(0., 0., 0.); outputs.len()];
                 for _ in 0..self.samples_per_pixel {
                     let ray = self.get_ray(x.into(), y.into());
-                    pixel_color += Self::ray_color(&ray, self.max_depth, world);
+                    for (pixel_color, (_, integrator)) in pixel_colors.iter_mut().zip(&outputs) {
+                        *pixel_color += integrator.ray_color(&ray, &scene);
+                    }
                 }
-
-                pixel_color *= self.pixel_samples_scale;
-                *pixel = pixel_color.into();
-            });
-
-        buffer
-            .save("image.png")
-            .expect("Failed to save buffer to image");
+                pixel_colors
+            })
+            .collect();
+
+        for (index, (path, integrator)) in outputs.iter().enumerate() {
+            let buffer: Rgb32FImage =
+                ImageBuffer::from_fn(self.image_width, self.image_height, |x, y| {
+                    let pixel = (y * self.image_width + x) as usize;
+                    let pixel_color = &pixels[pixel][index] * self.pixel_samples_scale;
+                    #[allow(clippy::cast_possible_truncation)]
+                    Rgb([0, 1, 2].map(|i| pixel_color[i] as f32))
+                });
+            Self::save(&buffer, path, integrator.outputs_color())
+                .expect("Failed to save buffer to image");
+        }
     }
 
-    fn ray_color(ray: &Ray, depth: u32, world: &dyn Hittable) -> Color {
-        // If we've exceeded the ray bounce limit, no more light is gathered
-        if depth == 0 {
-            return Color::new(0., 0., 0.);
+    /// Write `buffer` to `path`. Floating point formats, such as `.exr`, keep
+    /// the values as they are. Others are quantized to 8 bits, after gamma
+    /// encoding if `gamma_encode` is set.
+    fn save(buffer: &Rgb32FImage, path: &Path, gamma_encode: bool) -> ImageResult<()> {
+        if matches!(
+            ImageFormat::from_path(path),
+            Ok(ImageFormat::OpenExr | ImageFormat::Hdr)
+        ) {
+            return buffer.save(path);
         }
 
-        if let Some(hit_record) = world.hit(ray, &Interval::new(0.001, f64::INFINITY)) {
-            if let Some((attenuation, scattered)) = hit_record.material().scatter(ray, &hit_record)
-            {
-                return attenuation * Self::ray_color(&scattered, depth - 1, world);
+        let encoded: RgbImage = ImageBuffer::from_fn(buffer.width(), buffer.height(), |x, y| {
+            let [r, g, b] = buffer.get_pixel(x, y).0.map(f64::from);
+            if gamma_encode {
+                Color::new(r, g, b).into()
+            } else {
+                Rgb([to_byte(r), to_byte(g), to_byte(b)])
             }
-            return Color::new(0., 0., 0.);
-        }
-
-        let unit_direction = ray.direction().unit();
-        let a = 0.5 * (unit_direction.y() + 1.0);
-        let white = Color::new(1., 1., 1.);
-        let blue = Color::new(0.5, 0.7, 1.0);
-        (1. - a) * white + a * blue
+        });
+        encoded.save(path)
     }
 
     /// Construct a camera ray originating from the defocus disk and directed at
-    /// a randomly sampled point around the pixel location (i, j)
+    /// a randomly sampled point around the pixel location (i, j), at a random
+    /// time while the shutter is open
     fn get_ray(&self, i: f64, j: f64) -> Ray {
         let offset = Self::sample_square();
         let pixel_sample = &self.pixel00_loc
@@ -89,8 +135,10 @@ impl Camera {
             &self.defocus_disk_sample()
         };
         let ray_direction = pixel_sample - ray_origin;
+        let ray_time = (self.shutter_close - self.shutter_open)
+            .mul_add(rand::thread_rng().gen::<f64>(), self.shutter_open);
 
-        Ray::new(ray_origin.clone(), ray_direction)
+        Ray::new(ray_origin.clone(), ray_direction, ray_time)
     }
 
     /// Returns the vector to a random point in the [-.5,-.5]-[+.5,+.5] unit square
@@ -111,6 +159,7 @@ pub struct CamBuilder {
     image_width: u32,       // Rendered image width in pixel count
     samples_per_pixel: u32, // Count of random samples for each pixel
     max_depth: u32,         // Maximum number of ray bounces in scene
+    roulette_depth: u32,    // Bounces before paths may be ended at random
 
     vfov: f64,         // Vertical view angle (field of view)
     look_from: Point3, // Point camera is looking from
@@ -119,24 +168,41 @@ pub struct CamBuilder {
 
     defocus_angle: f64, // Variation angle of rays through each pixel
     focus_dist: f64,    // Distance from camera look_from point to plane of perfect focus
+
+    shutter_open: f64,  // Time at which the exposure starts
+    shutter_close: f64, // Time at which the exposure ends
+
+    background: Option<Box<dyn Background>>, // Color of rays that escape the scene
+    integrator: Option<Box<dyn Integrator>>, // Light transport algorithm run for each sample
+    output: Option<PathBuf>,                 // Where the rendered image is saved
+    passes: Vec<(PathBuf, Box<dyn Integrator>)>, // Extra images rendered alongside
 }
 
 impl CamBuilder {
+    #[must_use]
     pub const fn default() -> Self {
         Self {
             aspect_ratio: 1.0,
             image_width: 100,
             samples_per_pixel: 10,
             max_depth: 10,
+            roulette_depth: 3,
             vfov: 90.,
             look_from: Point3::new(0., 0., 0.),
             look_at: Point3::new(0., 0., -1.),
             vup: Vec3::new(0., 1., 0.),
             defocus_angle: 0.,
             focus_dist: 10.,
+            shutter_open: 0.,
+            shutter_close: 0.,
+            background: None,
+            integrator: None,
+            output: None,
+            passes: Vec::new(),
         }
     }
 
+    #[must_use]
     pub fn build(self) -> Camera {
         let image_height = Self::calculate_image_height(self.image_width.into(), self.aspect_ratio);
 
@@ -173,11 +239,23 @@ impl CamBuilder {
         let defocus_disk_u = &u * defocus_radius;
         let defocus_disk_v = &v * defocus_radius;
 
+        let background = self
+            .background
+            .unwrap_or_else(|| Box::new(GradientBackground::sky()));
+        let integrator = self.integrator.unwrap_or_else(|| {
+            Box::new(PathTracer::new(self.max_depth).roulette_depth(self.roulette_depth))
+        });
+
         Camera {
             image_width: self.image_width,
             samples_per_pixel: self.samples_per_pixel,
-            max_depth: self.max_depth,
             defocus_angle: self.defocus_angle,
+            shutter_open: self.shutter_open,
+            shutter_close: self.shutter_close,
+            background,
+            integrator,
+            output: self.output.unwrap_or_else(|| PathBuf::from("image.png")),
+            passes: self.passes,
             image_height,
             pixel_samples_scale,
             center,
@@ -199,53 +277,123 @@ impl CamBuilder {
         }
     }
 
+    #[must_use]
     pub const fn aspect_ratio(mut self, aspect_ratio: f64) -> Self {
         self.aspect_ratio = aspect_ratio;
         self
     }
 
+    #[must_use]
     pub const fn image_width(mut self, image_width: u32) -> Self {
         self.image_width = image_width;
         self
     }
 
+    #[must_use]
     pub const fn samples_per_pixel(mut self, samples_per_pixel: u32) -> Self {
         self.samples_per_pixel = samples_per_pixel;
         self
     }
 
+    /// Set the bounce limit of the default path tracer. Ignored when another
+    /// integrator is set.
+    #[must_use]
     pub const fn max_depth(mut self, max_depth: u32) -> Self {
         self.max_depth = max_depth;
         self
     }
 
+    /// Set how many bounces the default path tracer follows before Russian
+    /// roulette may end a path. Ignored when another integrator is set.
+    #[must_use]
+    pub const fn roulette_depth(mut self, roulette_depth: u32) -> Self {
+        self.roulette_depth = roulette_depth;
+        self
+    }
+
+    #[must_use]
     pub const fn vfov(mut self, vfov: f64) -> Self {
         self.vfov = vfov;
         self
     }
 
+    #[must_use]
     pub const fn look_from(mut self, look_from: Point3) -> Self {
         self.look_from = look_from;
         self
     }
 
+    #[must_use]
     pub const fn look_at(mut self, look_at: Point3) -> Self {
         self.look_at = look_at;
         self
     }
 
+    #[must_use]
     pub const fn vup(mut self, vup: Vec3) -> Self {
         self.vup = vup;
         self
     }
 
+    #[must_use]
     pub const fn defocus_angle(mut self, defocus_angle: f64) -> Self {
         self.defocus_angle = defocus_angle;
         self
     }
 
+    #[must_use]
     pub const fn focus_dist(mut self, focus_dist: f64) -> Self {
         self.focus_dist = focus_dist;
         self
     }
+
+    /// Set the times at which the exposure starts and ends. Rays are cast at
+    /// uniformly distributed times in between, blurring anything that moves.
+    #[must_use]
+    pub const fn shutter(mut self, open: f64, close: f64) -> Self {
+        self.shutter_open = open;
+        self.shutter_close = close;
+        self
+    }
+
+    /// Set what rays see when they escape the scene. Defaults to a sky gradient.
+    #[must_use]
+    pub fn background(mut self, background: Box<dyn Background>) -> Self {
+        self.background = Some(background);
+        self
+    }
+
+    /// Set the light transport algorithm used for every sample. Defaults to a
+    /// path tracer following up to `max_depth` bounces.
+    #[must_use]
+    pub fn integrator(mut self, integrator: Box<dyn Integrator>) -> Self {
+        self.integrator = Some(integrator);
+        self
+    }
+
+    /// Render a diagnostic view of the scene instead of lighting it, replacing
+    /// any integrator set before
+    #[must_use]
+    pub fn debug(mut self, mode: DebugMode) -> Self {
+        self.integrator = Some(Box::new(mode));
+        self
+    }
+
+    /// Set where the rendered image is saved, in the format given by the
+    /// extension. Defaults to `image.png`. Use `.exr` or `.hdr` to keep
+    /// values above 1, such as bright highlights or raw distances.
+    #[must_use]
+    pub fn output(mut self, path: impl Into<PathBuf>) -> Self {
+        self.output = Some(path.into());
+        self
+    }
+
+    /// Also render the scene with `integrator` and save it to `path`, such as
+    /// an ambient occlusion or normals pass. Passes see the same camera rays as
+    /// the main image.
+    #[must_use]
+    pub fn pass(mut self, path: impl Into<PathBuf>, integrator: Box<dyn Integrator>) -> Self {
+        self.passes.push((path.into(), integrator));
+        self
+    }
 }
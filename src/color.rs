@@ -1,3 +1,7 @@
+use crate::interval::Interval;
+use crate::vec3::Color;
+
+#[must_use]
 pub fn linear_to_gamma(linear_component: f64) -> f64 {
     if linear_component > 0. {
         linear_component.sqrt()
@@ -5,3 +9,28 @@ pub fn linear_to_gamma(linear_component: f64) -> f64 {
         0.
     }
 }
+
+/// Quantizes a color component to 8 bits, clamping it to [0, 1]
+#[must_use]
+pub fn to_byte(component: f64) -> u8 {
+    let intensity = Interval::new(0.000, 0.999);
+    #[allow(clippy::cast_sign_loss, clippy::cast_possible_truncation)]
+    let byte = (256. * intensity.clamp(component)) as u8;
+    byte
+}
+
+/// Decodes an sRGB-encoded color component, as stored in most 8-bit images, to linear
+#[must_use]
+pub fn srgb_to_linear(encoded_component: f64) -> f64 {
+    if encoded_component <= 0.04045 {
+        encoded_component / 12.92
+    } else {
+        ((encoded_component + 0.055) / 1.055).powf(2.4)
+    }
+}
+
+/// Relative luminance of a linear color, with Rec. 709 primaries
+#[must_use]
+pub fn luminance(color: &Color) -> f64 {
+    0.2126_f64.mul_add(color.x(), 0.7152_f64.mul_add(color.y(), 0.0722 * color.z()))
+}
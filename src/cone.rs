@@ -0,0 +1,109 @@
+use std::f64::consts::PI;
+
+use crate::aabb::Aabb;
+use crate::disk::{disk_bbox, intersect_disk_local};
+use crate::hittable::{HitRecord, Hittable};
+use crate::interval::Interval;
+use crate::material::Material;
+use crate::onb::Onb;
+use crate::polynomial::solve_quadratic;
+use crate::ray::Ray;
+use crate::vec3::{Point3, Vec3};
+
+/// Right circular cone with its base disk at `base` and its apex at
+/// `base + axis`. Capped at the base unless made `open`.
+pub struct Cone {
+    base: Point3,
+    onb: Onb, // `w` runs along the axis, towards the apex
+    height: f64,
+    radius: f64, // Radius of the base
+    capped: bool,
+    mat: Material,
+    bbox: Aabb,
+}
+
+impl Cone {
+    #[must_use]
+    pub fn new(base: Point3, axis: &Vec3, radius: f64, mat: Material) -> Self {
+        let onb = Onb::new(axis);
+        let apex = &base + axis;
+        let bbox = Aabb::union(
+            &disk_bbox(&base, onb.w(), radius),
+            &Aabb::from_points(&apex, &apex),
+        );
+        Self {
+            base,
+            onb,
+            height: axis.length(),
+            radius,
+            capped: true,
+            mat,
+            bbox,
+        }
+    }
+
+    /// Remove the base cap
+    #[must_use]
+    pub const fn open(mut self) -> Self {
+        self.capped = false;
+        self
+    }
+}
+
+impl Hittable for Cone {
+    #[allow(clippy::many_single_char_names)]
+    fn hit(&self, ray: &Ray, ray_t: &Interval) -> Option<HitRecord> {
+        let origin = self.onb.to_local(&(ray.origin() - &self.base));
+        let direction = self.onb.to_local(ray.direction());
+
+        // Closest hit so far, as (t, local outward normal, uv)
+        let mut closest: Option<(f64, Vec3, (f64, f64))> = None;
+
+        // Side: x^2 + y^2 = (k (height - z))^2 with 0 <= z <= height, where k is
+        // the slope of the radius
+        let k_sq = (self.radius / self.height).powi(2);
+        let h = self.height - origin.z();
+        let a = direction.x().mul_add(direction.x(), direction.y().powi(2))
+            - k_sq * direction.z().powi(2);
+        let half_b = origin.x().mul_add(
+            direction.x(),
+            origin.y().mul_add(direction.y(), k_sq * h * direction.z()),
+        );
+        let c = (k_sq * h).mul_add(-h, origin.x().mul_add(origin.x(), origin.y().powi(2)));
+        for t in solve_quadratic(a, 2. * half_b, c) {
+            let p = &origin + &(t * &direction);
+            if ray_t.surrounds(t) && (0. ..=self.height).contains(&p.z()) {
+                let normal = Vec3::new(p.x(), p.y(), k_sq * (self.height - p.z()));
+                let normal = if normal.near_zero() {
+                    // The apex has no well-defined normal; point it along the axis
+                    Vec3::new(0., 0., 1.)
+                } else {
+                    normal.unit()
+                };
+                let uv = ((p.y().atan2(p.x()) + PI) / (2. * PI), p.z() / self.height);
+                closest = Some((t, normal, uv));
+                break;
+            }
+        }
+
+        if self.capped {
+            if let Some((t, x, y)) = intersect_disk_local(&origin, &direction, 0., self.radius) {
+                if ray_t.surrounds(t) && closest.as_ref().is_none_or(|(best, _, _)| t < *best) {
+                    let uv = ((y.atan2(x) + PI) / (2. * PI), x.hypot(y) / self.radius);
+                    closest = Some((t, Vec3::new(0., 0., -1.), uv));
+                }
+            }
+        }
+
+        let (t, local_normal, (u, v)) = closest?;
+        let mut hit_record = HitRecord::new(ray.at(t), Vec3::new(0., 0., 0.), self.mat.clone(), t);
+        hit_record.set_face_normal(ray, self.onb.transform(&local_normal));
+        hit_record.set_uv(u, v);
+
+        Some(hit_record)
+    }
+
+    fn bounding_box(&self) -> &Aabb {
+        &self.bbox
+    }
+}
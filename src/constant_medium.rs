@@ -0,0 +1,68 @@
+use std::sync::Arc;
+
+use rand::Rng;
+
+use crate::aabb::Aabb;
+use crate::hittable::{HitRecord, Hittable};
+use crate::interval::Interval;
+use crate::material::Material;
+use crate::ray::Ray;
+use crate::texture::Texture;
+use crate::vec3::Vec3;
+
+/// Homogeneous participating medium, such as fog or smoke, filling the inside
+/// of a closed boundary object
+pub struct ConstantMedium {
+    boundary: Box<dyn Hittable>,
+    neg_inv_density: f64,
+    phase_function: Material,
+}
+
+impl ConstantMedium {
+    pub fn new(boundary: Box<dyn Hittable>, density: f64, albedo: Arc<dyn Texture>) -> Self {
+        Self {
+            boundary,
+            neg_inv_density: -1. / density,
+            phase_function: Material::Isotropic { albedo },
+        }
+    }
+}
+
+impl Hittable for ConstantMedium {
+    fn hit(&self, ray: &Ray, ray_t: &Interval) -> Option<HitRecord> {
+        // Find where the ray enters and leaves the boundary, even if it starts inside
+        let rec1 = self.boundary.hit(ray, &Interval::universe())?;
+        let rec2 = self
+            .boundary
+            .hit(ray, &Interval::new(rec1.t() + 0.0001, f64::INFINITY))?;
+
+        let t_enter = rec1.t().max(ray_t.min());
+        let t_exit = rec2.t().min(ray_t.max());
+        if t_enter >= t_exit {
+            return None;
+        }
+        let t_enter = t_enter.max(0.);
+
+        // Sample an exponentially distributed distance to the next scattering event
+        let ray_length = ray.direction().length();
+        let distance_inside_boundary = (t_exit - t_enter) * ray_length;
+        let hit_distance = self.neg_inv_density * rand::thread_rng().gen::<f64>().ln();
+        if hit_distance > distance_inside_boundary {
+            return None;
+        }
+
+        let t = t_enter + hit_distance / ray_length;
+
+        // The normal and face are arbitrary, as the phase function ignores them
+        Some(HitRecord::new(
+            ray.at(t),
+            Vec3::new(1., 0., 0.),
+            self.phase_function.clone(),
+            t,
+        ))
+    }
+
+    fn bounding_box(&self) -> &Aabb {
+        self.boundary.bounding_box()
+    }
+}
@@ -0,0 +1,123 @@
+use crate::aabb::Aabb;
+use crate::hittable::{HitRecord, Hittable};
+use crate::interval::Interval;
+use crate::ray::Ray;
+
+#[derive(Clone, Copy)]
+pub enum CsgOp {
+    Union,
+    Intersection,
+    Difference,
+}
+
+impl CsgOp {
+    /// Whether a point is inside the combined solid, given whether it is inside each operand
+    const fn contains(self, in_left: bool, in_right: bool) -> bool {
+        match self {
+            Self::Union => in_left || in_right,
+            Self::Intersection => in_left && in_right,
+            Self::Difference => in_left && !in_right,
+        }
+    }
+}
+
+/// Constructive solid geometry: a boolean combination of two closed objects
+pub struct Csg {
+    left: Box<dyn Hittable>,
+    right: Box<dyn Hittable>,
+    op: CsgOp,
+    bbox: Aabb,
+}
+
+impl Csg {
+    #[must_use]
+    pub fn new(left: Box<dyn Hittable>, right: Box<dyn Hittable>, op: CsgOp) -> Self {
+        let bbox = match op {
+            CsgOp::Union => Aabb::union(left.bounding_box(), right.bounding_box()),
+            // The result never extends beyond the left operand
+            CsgOp::Intersection | CsgOp::Difference => left.bounding_box().clone(),
+        };
+        Self {
+            left,
+            right,
+            op,
+            bbox,
+        }
+    }
+
+    /// Points inside either object
+    #[must_use]
+    pub fn union(left: Box<dyn Hittable>, right: Box<dyn Hittable>) -> Self {
+        Self::new(left, right, CsgOp::Union)
+    }
+
+    /// Points inside both objects
+    #[must_use]
+    pub fn intersection(left: Box<dyn Hittable>, right: Box<dyn Hittable>) -> Self {
+        Self::new(left, right, CsgOp::Intersection)
+    }
+
+    /// Points inside `left` but not inside `right`
+    #[must_use]
+    pub fn difference(left: Box<dyn Hittable>, right: Box<dyn Hittable>) -> Self {
+        Self::new(left, right, CsgOp::Difference)
+    }
+}
+
+/// Whether the ray origin starts inside an object, judging by the first crossing
+fn starts_inside(crossings: &[HitRecord]) -> bool {
+    crossings.first().is_some_and(|first| !first.front_face())
+}
+
+impl Hittable for Csg {
+    fn hit(&self, ray: &Ray, ray_t: &Interval) -> Option<HitRecord> {
+        self.hit_all(ray, ray_t).into_iter().next()
+    }
+
+    fn bounding_box(&self) -> &Aabb {
+        &self.bbox
+    }
+
+    fn hit_all(&self, ray: &Ray, ray_t: &Interval) -> Vec<HitRecord> {
+        if !self.bbox.hit(ray, ray_t) {
+            return Vec::new();
+        }
+
+        // Crossings are gathered along the whole line so we know which operands
+        // the ray is already inside of when it reaches `ray_t`
+        let left = self.left.hit_all(ray, &Interval::universe());
+        let right = self.right.hit_all(ray, &Interval::universe());
+
+        let mut in_left = starts_inside(&left);
+        let mut in_right = starts_inside(&right);
+        let mut inside = self.op.contains(in_left, in_right);
+
+        let mut events: Vec<(HitRecord, bool)> = left
+            .into_iter()
+            .map(|hit_record| (hit_record, true))
+            .chain(right.into_iter().map(|hit_record| (hit_record, false)))
+            .collect();
+        events.sort_by(|(a, _), (b, _)| a.t().total_cmp(&b.t()));
+
+        // Keep only the crossings where the combined solid's inside/outside state changes
+        let mut crossings = Vec::new();
+        for (mut hit_record, is_left) in events {
+            if is_left {
+                in_left = hit_record.front_face();
+            } else {
+                in_right = hit_record.front_face();
+            }
+
+            let now_inside = self.op.contains(in_left, in_right);
+            if now_inside != inside && ray_t.surrounds(hit_record.t()) {
+                // The normal still faces the ray; only the side may have flipped,
+                // e.g. where the right operand is carved out of the left
+                hit_record.set_front_face(now_inside);
+                crossings.push(hit_record);
+            }
+            inside = now_inside;
+        }
+
+        crossings
+    }
+}
@@ -0,0 +1,105 @@
+use std::f64::consts::PI;
+
+use crate::aabb::Aabb;
+use crate::disk::{disk_bbox, intersect_disk_local};
+use crate::hittable::{HitRecord, Hittable};
+use crate::interval::Interval;
+use crate::material::Material;
+use crate::onb::Onb;
+use crate::polynomial::solve_quadratic;
+use crate::ray::Ray;
+use crate::vec3::{Point3, Vec3};
+
+/// Circular cylinder running from `base` along `axis`, whose length is the
+/// cylinder's height. Capped at both ends unless made `open`.
+pub struct Cylinder {
+    base: Point3,
+    onb: Onb, // `w` runs along the axis
+    height: f64,
+    radius: f64,
+    capped: bool,
+    mat: Material,
+    bbox: Aabb,
+}
+
+impl Cylinder {
+    #[must_use]
+    pub fn new(base: Point3, axis: &Vec3, radius: f64, mat: Material) -> Self {
+        let onb = Onb::new(axis);
+        let top = &base + axis;
+        let bbox = Aabb::union(
+            &disk_bbox(&base, onb.w(), radius),
+            &disk_bbox(&top, onb.w(), radius),
+        );
+        Self {
+            base,
+            onb,
+            height: axis.length(),
+            radius,
+            capped: true,
+            mat,
+            bbox,
+        }
+    }
+
+    /// Remove the end caps, leaving a tube
+    #[must_use]
+    pub const fn open(mut self) -> Self {
+        self.capped = false;
+        self
+    }
+}
+
+impl Hittable for Cylinder {
+    #[allow(clippy::many_single_char_names)]
+    fn hit(&self, ray: &Ray, ray_t: &Interval) -> Option<HitRecord> {
+        let origin = self.onb.to_local(&(ray.origin() - &self.base));
+        let direction = self.onb.to_local(ray.direction());
+
+        // Closest hit so far, as (t, local outward normal, uv)
+        let mut closest: Option<(f64, Vec3, (f64, f64))> = None;
+
+        // Side: x^2 + y^2 = r^2 with 0 <= z <= height
+        let a = direction.x().mul_add(direction.x(), direction.y().powi(2));
+        let half_b = origin
+            .x()
+            .mul_add(direction.x(), origin.y() * direction.y());
+        let c = self.radius.mul_add(
+            -self.radius,
+            origin.x().mul_add(origin.x(), origin.y().powi(2)),
+        );
+        for t in solve_quadratic(a, 2. * half_b, c) {
+            let p = &origin + &(t * &direction);
+            if ray_t.surrounds(t) && (0. ..=self.height).contains(&p.z()) {
+                let normal = Vec3::new(p.x() / self.radius, p.y() / self.radius, 0.);
+                let uv = ((p.y().atan2(p.x()) + PI) / (2. * PI), p.z() / self.height);
+                closest = Some((t, normal, uv));
+                break;
+            }
+        }
+
+        if self.capped {
+            for (z, normal_z) in [(0., -1.), (self.height, 1.)] {
+                let Some((t, x, y)) = intersect_disk_local(&origin, &direction, z, self.radius)
+                else {
+                    continue;
+                };
+                if ray_t.surrounds(t) && closest.as_ref().is_none_or(|(best, _, _)| t < *best) {
+                    let uv = ((y.atan2(x) + PI) / (2. * PI), x.hypot(y) / self.radius);
+                    closest = Some((t, Vec3::new(0., 0., normal_z), uv));
+                }
+            }
+        }
+
+        let (t, local_normal, (u, v)) = closest?;
+        let mut hit_record = HitRecord::new(ray.at(t), Vec3::new(0., 0., 0.), self.mat.clone(), t);
+        hit_record.set_face_normal(ray, self.onb.transform(&local_normal));
+        hit_record.set_uv(u, v);
+
+        Some(hit_record)
+    }
+
+    fn bounding_box(&self) -> &Aabb {
+        &self.bbox
+    }
+}
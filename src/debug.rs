@@ -0,0 +1,109 @@
+use crate::integrator::{Integrator, Scene};
+use crate::ray::Ray;
+use crate::vec3::{Color, Vec3};
+
+/// Diagnostic views of a scene, rendered through the camera in place of
+/// light transport. Rays that miss everything are black.
+pub enum DebugMode {
+    /// Shading normals at the first hit, mapped from [-1, 1] to [0, 1]
+    Normals,
+    /// Distance to the first hit. Divided by `max_distance` to fade from black
+    /// to white, or raw with `None`, best saved to a floating point image.
+    Depth { max_distance: Option<f64> },
+    /// Base color of the material at the first hit
+    Albedo,
+    /// Surface coordinates of the first hit, in the red and green channels
+    Uv,
+    /// A distinct color for each object, by the position it was added to the world at
+    ObjectIndex,
+    /// Heatmap of how often paths bounce, from black for none to red for `max_depth`
+    Bounces { max_depth: u32 },
+    /// Heatmap of the bounding volume hierarchy nodes tested by camera rays,
+    /// from black for none to red for `max_visits`
+    BvhVisits { max_visits: u64 },
+}
+
+impl Integrator for DebugMode {
+    #[allow(clippy::cast_precision_loss)]
+    fn ray_color(&self, ray: &Ray, scene: &Scene) -> Color {
+        let black = Color::new(0., 0., 0.);
+        match self {
+            // A zero scale would divide by zero, so it saturates at a single step instead
+            Self::Bounces { max_depth } => {
+                let scale = f64::from((*max_depth).max(1));
+                heatmap(f64::from(bounces(ray, scene, *max_depth)) / scale)
+            }
+            Self::BvhVisits { max_visits } => {
+                let (_, trace) = scene.hit_traced(ray);
+                heatmap(trace.node_visits() as f64 / (*max_visits).max(1) as f64)
+            }
+            Self::ObjectIndex => {
+                let (hit_record, trace) = scene.hit_traced(ray);
+                hit_record.map_or(black, |_| object_color(trace.object()))
+            }
+            _ => {
+                let Some(hit_record) = scene.hit(ray) else {
+                    return black;
+                };
+                match self {
+                    Self::Normals => 0.5 * (hit_record.normal() + Vec3::new(1., 1., 1.)),
+                    Self::Depth { max_distance } => {
+                        let distance = hit_record.t() * ray.direction().length();
+                        let depth = max_distance.map_or(distance, |max| distance / max);
+                        Color::new(depth, depth, depth)
+                    }
+                    Self::Albedo => hit_record.material().albedo(&hit_record),
+                    Self::Uv => Color::new(hit_record.u(), hit_record.v(), 0.),
+                    Self::ObjectIndex | Self::Bounces { .. } | Self::BvhVisits { .. } => black,
+                }
+            }
+        }
+    }
+
+    fn outputs_color(&self) -> bool {
+        matches!(self, Self::Albedo)
+    }
+}
+
+/// Number of times a path starting with `ray` scatters before it escapes, is
+/// absorbed, or reaches `max_depth` bounces
+fn bounces(ray: &Ray, scene: &Scene, max_depth: u32) -> u32 {
+    let mut ray = ray.clone();
+    for depth in 0..max_depth {
+        let Some(hit_record) = scene.hit(&ray) else {
+            return depth;
+        };
+        let Some((_, scattered)) = hit_record.material().scatter(&ray, &hit_record) else {
+            return depth;
+        };
+        ray = scattered;
+    }
+    max_depth
+}
+
+/// Maps `value` in [0, 1] through black, blue, green and yellow to red
+fn heatmap(value: f64) -> Color {
+    const STOPS: [Color; 5] = [
+        Color::new(0., 0., 0.),
+        Color::new(0., 0., 1.),
+        Color::new(0., 1., 0.),
+        Color::new(1., 1., 0.),
+        Color::new(1., 0., 0.),
+    ];
+
+    let position = value.clamp(0., 1.) * 4.;
+    #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+    let index = (position as usize).min(3);
+    #[allow(clippy::cast_precision_loss)]
+    let fraction = position - index as f64;
+    (1. - fraction) * &STOPS[index] + fraction * &STOPS[index + 1]
+}
+
+/// Fully saturated color for an object. Stepping the hue by the golden ratio
+/// keeps objects added one after the other far apart.
+#[allow(clippy::cast_precision_loss)]
+fn object_color(index: usize) -> Color {
+    let hue = (index as f64 * 0.618_033_988_749_895).fract() * 6.;
+    let channel = |offset: f64| ((((hue + offset) % 6.) - 3.).abs() - 1.).clamp(0., 1.);
+    Color::new(channel(0.), channel(4.), channel(2.))
+}
@@ -0,0 +1,115 @@
+use std::f64::consts::PI;
+
+use rand::Rng;
+
+use crate::aabb::Aabb;
+use crate::hittable::{HitRecord, Hittable, area_to_solid_angle_pdf};
+use crate::interval::Interval;
+use crate::material::Material;
+use crate::onb::Onb;
+use crate::ray::Ray;
+use crate::vec3::{Point3, Vec3};
+
+/// Flat circular disk facing along `normal`
+pub struct Disk {
+    center: Point3,
+    onb: Onb, // `w` is the disk normal
+    radius: f64,
+    mat: Material,
+    bbox: Aabb,
+}
+
+impl Disk {
+    #[must_use]
+    pub fn new(center: Point3, normal: &Vec3, radius: f64, mat: Material) -> Self {
+        let onb = Onb::new(normal);
+        let bbox = disk_bbox(&center, onb.w(), radius);
+        Self {
+            center,
+            onb,
+            radius,
+            mat,
+            bbox,
+        }
+    }
+}
+
+impl Hittable for Disk {
+    fn hit(&self, ray: &Ray, ray_t: &Interval) -> Option<HitRecord> {
+        let origin = self.onb.to_local(&(ray.origin() - &self.center));
+        let direction = self.onb.to_local(ray.direction());
+
+        let (t, x, y) = intersect_disk_local(&origin, &direction, 0., self.radius)?;
+        if !ray_t.surrounds(t) {
+            return None;
+        }
+
+        let mut hit_record = HitRecord::new(ray.at(t), Vec3::new(0., 0., 0.), self.mat.clone(), t);
+        hit_record.set_face_normal(ray, self.onb.w().clone());
+        hit_record.set_uv((y.atan2(x) + PI) / (2. * PI), x.hypot(y) / self.radius);
+
+        Some(hit_record)
+    }
+
+    fn bounding_box(&self) -> &Aabb {
+        &self.bbox
+    }
+
+    fn sample(&self, origin: &Point3, time: f64) -> Option<(Vec3, f64)> {
+        let mut rng = rand::thread_rng();
+        // The square root spreads points evenly over the area rather than the radius
+        let r = self.radius * rng.gen::<f64>().sqrt();
+        let phi = 2. * PI * rng.gen::<f64>();
+        let point = &self.center
+            + self
+                .onb
+                .transform(&Vec3::new(r * phi.cos(), r * phi.sin(), 0.));
+
+        let direction = point - origin;
+        let pdf = self.pdf(origin, &direction, time);
+        (pdf > 0.).then_some((direction, pdf))
+    }
+
+    fn pdf(&self, origin: &Point3, direction: &Vec3, time: f64) -> f64 {
+        let ray = Ray::new(origin.clone(), direction.clone(), time);
+        self.hit(&ray, &Interval::new(0.001, f64::INFINITY))
+            .map_or(0., |hit_record| {
+                area_to_solid_angle_pdf(&ray, &hit_record, PI * self.radius * self.radius)
+            })
+    }
+}
+
+/// Intersects a ray, given in a basis where the disk normal is the z axis, with
+/// a disk of `radius` centered on that axis at height `z`. Returns the ray
+/// parameter and the local x and y of the hit.
+pub(crate) fn intersect_disk_local(
+    origin: &Vec3,
+    direction: &Vec3,
+    z: f64,
+    radius: f64,
+) -> Option<(f64, f64, f64)> {
+    if direction.z().abs() < 1e-12 {
+        return None;
+    }
+
+    let t = (z - origin.z()) / direction.z();
+    let x = t.mul_add(direction.x(), origin.x());
+    let y = t.mul_add(direction.y(), origin.y());
+    if x.mul_add(x, y * y) > radius * radius {
+        return None;
+    }
+
+    Some((t, x, y))
+}
+
+/// Tight bounding box of a disk; along each world axis, its extent is the
+/// radius scaled by how far that axis is from the disk normal
+pub(crate) fn disk_bbox(center: &Point3, unit_normal: &Vec3, radius: f64) -> Aabb {
+    let extent = |n: f64| radius * n.mul_add(-n, 1.).max(0.).sqrt();
+    let half = Vec3::new(
+        extent(unit_normal.x()),
+        extent(unit_normal.y()),
+        extent(unit_normal.z()),
+    );
+    Aabb::from_points(&(center - &half), &(center + &half))
+}
@@ -0,0 +1,208 @@
+use std::f64::consts::PI;
+use std::path::Path;
+
+use image::ImageError;
+use rand::Rng;
+
+use crate::background::Background;
+use crate::color::luminance;
+use crate::image_texture::load_linear;
+use crate::vec3::{Color, Vec3};
+
+/// Background from an equirectangular (latitude-longitude) image, such as an HDRI panorama
+///
+/// The top row of the image is straight up, the middle row the horizon, and
+/// the center column looks down -x before any rotation.
+///
+/// Directions are importance sampled in proportion to pixel luminance, so that
+/// small bright features such as the sun are found directly rather than by chance.
+pub struct EnvironmentMap {
+    width: usize,
+    height: usize,
+    pixels: Vec<Color>,
+    rotation: f64, // Degrees counterclockwise about +y, seen from above
+    intensity: f64,
+
+    weights: Vec<f64>,    // Sampling weight of each pixel, luminance * sin(theta)
+    total_weight: f64,    // Sum of all the weights
+    row_cdf: Vec<f64>,    // Cumulative distribution over rows
+    column_cdf: Vec<f64>, // Cumulative distribution over the columns of each row
+}
+
+impl EnvironmentMap {
+    /// Create an environment map from linear colors, stored row by row from the top
+    ///
+    /// # Panics
+    ///
+    /// Panics if the image is empty or the number of pixels doesn't match its size.
+    #[must_use]
+    pub fn new(width: usize, height: usize, pixels: Vec<Color>) -> Self {
+        assert!(width > 0 && height > 0, "Environment map cannot be empty");
+        assert_eq!(
+            pixels.len(),
+            width * height,
+            "Environment map needs one color per pixel"
+        );
+
+        // Rows near the poles cover less solid angle, so weigh them by sin(theta).
+        // A black map falls back to sampling the sphere uniformly.
+        let row_sin = |row: usize| (Self::row_theta(row, height)).sin();
+        let mut weights: Vec<f64> = pixels
+            .iter()
+            .enumerate()
+            .map(|(index, pixel)| luminance(pixel).max(0.) * row_sin(index / width))
+            .collect();
+        if weights.iter().sum::<f64>() <= 0. {
+            weights = (0..pixels.len())
+                .map(|index| row_sin(index / width))
+                .collect();
+        }
+
+        let mut row_cdf = Vec::with_capacity(height);
+        let mut column_cdf = Vec::with_capacity(width * height);
+        let mut total_weight = 0.;
+        for row in weights.chunks(width) {
+            let row_weight: f64 = row.iter().sum();
+            let mut accum = 0.;
+            for weight in row {
+                accum += weight;
+                column_cdf.push(if row_weight > 0. {
+                    accum / row_weight
+                } else {
+                    0.
+                });
+            }
+            total_weight += row_weight;
+            row_cdf.push(total_weight);
+        }
+        for value in &mut row_cdf {
+            *value /= total_weight;
+        }
+
+        Self {
+            width,
+            height,
+            pixels,
+            rotation: 0.,
+            intensity: 1.,
+            weights,
+            total_weight,
+            row_cdf,
+            column_cdf,
+        }
+    }
+
+    /// Load an equirectangular `.hdr` or `.exr` panorama. Low dynamic range
+    /// images also work, and are decoded from sRGB.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the image cannot be read or decoded.
+    pub fn open(path: impl AsRef<Path>) -> Result<Self, ImageError> {
+        let (width, height, pixels) = load_linear(path)?;
+        Ok(Self::new(width, height, pixels))
+    }
+
+    /// Spin the map about the vertical axis by `degrees`, counterclockwise seen from above
+    #[must_use]
+    pub const fn rotation(mut self, degrees: f64) -> Self {
+        self.rotation = degrees;
+        self
+    }
+
+    /// Scale the brightness of the whole map
+    #[must_use]
+    pub const fn intensity(mut self, intensity: f64) -> Self {
+        self.intensity = intensity;
+        self
+    }
+
+    /// Polar angle from +y through the center of `row`
+    #[allow(clippy::cast_precision_loss)]
+    fn row_theta(row: usize, height: usize) -> f64 {
+        PI * (row as f64 + 0.5) / height as f64
+    }
+
+    /// Image coordinates in [0, 1) of the unit `direction`, from the top left
+    fn direction_to_uv(&self, direction: &Vec3) -> (f64, f64) {
+        let theta = direction.y().clamp(-1., 1.).acos();
+        let phi = direction.z().atan2(-direction.x()) - self.rotation.to_radians();
+        let u = (phi / (2. * PI) + 0.5).rem_euclid(1.);
+        (u, theta / PI)
+    }
+
+    /// Unit direction of the image coordinates `(u, v)`, from the top left
+    fn uv_to_direction(&self, u: f64, v: f64) -> Vec3 {
+        let theta = v * PI;
+        let phi = (u - 0.5).mul_add(2. * PI, self.rotation.to_radians());
+        let (sin_theta, cos_theta) = theta.sin_cos();
+        let (sin_phi, cos_phi) = phi.sin_cos();
+        Vec3::new(-sin_theta * cos_phi, cos_theta, sin_theta * sin_phi)
+    }
+
+    /// Index of the pixel containing the image coordinates `(u, v)`
+    #[allow(
+        clippy::cast_precision_loss,
+        clippy::cast_possible_truncation,
+        clippy::cast_sign_loss
+    )]
+    fn pixel_index(&self, u: f64, v: f64) -> usize {
+        let column = ((u * self.width as f64) as usize).min(self.width - 1);
+        let row = ((v * self.height as f64) as usize).min(self.height - 1);
+        row * self.width + column
+    }
+
+    /// Converts the probability of picking a pixel to a density over solid angle
+    /// at polar angle `theta`. Each pixel spans (2 pi / width) by (pi / height)
+    /// in longitude and latitude, shrinking by sin(theta) towards the poles.
+    #[allow(clippy::cast_precision_loss)]
+    fn solid_angle_pdf(&self, pixel_probability: f64, theta: f64) -> f64 {
+        let sin_theta = theta.sin();
+        if sin_theta <= 0. {
+            return 0.;
+        }
+        pixel_probability * (self.width * self.height) as f64 / (2. * PI * PI * sin_theta)
+    }
+}
+
+/// First index whose cumulative value exceeds `xi`
+fn search(cdf: &[f64], xi: f64) -> usize {
+    cdf.partition_point(|&value| value <= xi).min(cdf.len() - 1)
+}
+
+impl Background for EnvironmentMap {
+    fn color(&self, direction: &Vec3) -> Color {
+        let (u, v) = self.direction_to_uv(direction);
+        self.intensity * &self.pixels[self.pixel_index(u, v)]
+    }
+
+    fn is_samplable(&self) -> bool {
+        true
+    }
+
+    #[allow(clippy::cast_precision_loss)]
+    fn sample(&self) -> Option<(Vec3, f64)> {
+        let mut rng = rand::thread_rng();
+
+        let row = search(&self.row_cdf, rng.gen());
+        let columns = &self.column_cdf[row * self.width..(row + 1) * self.width];
+        let column = search(columns, rng.gen());
+
+        // Pick a uniformly random point inside the chosen pixel
+        let u = (column as f64 + rng.gen::<f64>()) / self.width as f64;
+        let v = (row as f64 + rng.gen::<f64>()) / self.height as f64;
+
+        let pixel_probability = self.weights[row * self.width + column] / self.total_weight;
+        let pdf = self.solid_angle_pdf(pixel_probability, v * PI);
+        if pdf <= 0. {
+            return None;
+        }
+        Some((self.uv_to_direction(u, v), pdf))
+    }
+
+    fn pdf(&self, direction: &Vec3) -> f64 {
+        let (u, v) = self.direction_to_uv(direction);
+        let pixel_probability = self.weights[self.pixel_index(u, v)] / self.total_weight;
+        self.solid_angle_pdf(pixel_probability, v * PI)
+    }
+}
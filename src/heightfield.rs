@@ -0,0 +1,405 @@
+use std::path::Path;
+
+use image::ImageError;
+use image::error::{ParameterError, ParameterErrorKind};
+
+use crate::aabb::Aabb;
+use crate::hittable::{HitRecord, Hittable};
+use crate::interval::Interval;
+use crate::material::Material;
+use crate::ray::Ray;
+use crate::triangle::{intersect_watertight, triangle_hit_record};
+use crate::vec3::{Point3, Vec3};
+
+/// Minimum and maximum height over each cell of one level of the mipmap pyramid
+struct MinMaxLevel {
+    width: usize,
+    depth: usize,
+    min: Vec<f64>,
+    max: Vec<f64>,
+}
+
+impl MinMaxLevel {
+    /// Halves the resolution of `finer`, each cell covering up to 2x2 of its cells
+    fn coarsen(finer: &Self) -> Self {
+        let width = finer.width.div_ceil(2);
+        let depth = finer.depth.div_ceil(2);
+        let mut min = vec![f64::INFINITY; width * depth];
+        let mut max = vec![f64::NEG_INFINITY; width * depth];
+
+        for j in 0..finer.depth {
+            for i in 0..finer.width {
+                let cell = (j / 2) * width + i / 2;
+                min[cell] = min[cell].min(finer.min[j * finer.width + i]);
+                max[cell] = max[cell].max(finer.max[j * finer.width + i]);
+            }
+        }
+
+        Self {
+            width,
+            depth,
+            min,
+            max,
+        }
+    }
+}
+
+/// Terrain given by a regular grid of height samples over the xz plane. Each
+/// grid cell is split into two triangles with interpolated vertex normals.
+///
+/// Rays are traced through a min/max mipmap of the grid: a hierarchical 2D DDA
+/// walks the cells of each level in ray order and only descends into cells
+/// whose height range the ray actually passes through.
+pub struct Heightfield {
+    origin: Point3, // Position of the first sample at height zero
+    cell_size: (f64, f64),
+    resolution: (usize, usize), // Number of samples along x and z
+    heights: Vec<f64>,          // Row-major, one row per z sample
+    normals: Vec<Vec3>,
+    levels: Vec<MinMaxLevel>, // Finest first; the last level is a single cell
+    mat: Material,
+    bbox: Aabb,
+}
+
+impl Heightfield {
+    /// Create a heightfield from `resolution.0 * resolution.1` samples, stored
+    /// row by row along x, spanning `size` world units in x and z from `origin`
+    ///
+    /// # Panics
+    ///
+    /// Panics if there are fewer than two samples along either axis, or if the
+    /// number of heights doesn't match the resolution.
+    #[must_use]
+    pub fn new(
+        origin: Point3,
+        size: (f64, f64),
+        resolution: (usize, usize),
+        heights: Vec<f64>,
+        mat: Material,
+    ) -> Self {
+        let (nx, nz) = resolution;
+        assert!(nx >= 2 && nz >= 2, "Heightfield needs at least 2x2 samples");
+        assert_eq!(
+            heights.len(),
+            nx * nz,
+            "Heightfield needs one height per sample"
+        );
+
+        #[allow(clippy::cast_precision_loss)]
+        let cell_size = (size.0 / (nx - 1) as f64, size.1 / (nz - 1) as f64);
+
+        let mut field = Self {
+            origin,
+            cell_size,
+            resolution,
+            heights,
+            normals: Vec::new(),
+            levels: Vec::new(),
+            mat,
+            bbox: Aabb::empty(),
+        };
+        field.normals = field.vertex_normals();
+        field.levels = field.min_max_levels();
+
+        let top = &field.levels[field.levels.len() - 1];
+        let low = Point3::new(
+            field.origin.x(),
+            field.origin.y() + top.min[0],
+            field.origin.z(),
+        );
+        let high = Point3::new(
+            field.origin.x() + size.0,
+            field.origin.y() + top.max[0],
+            field.origin.z() + size.1,
+        );
+        field.bbox = Aabb::from_points(&low, &high);
+
+        field
+    }
+
+    /// Sample a procedural height function `f(x, z)`, taking world coordinates
+    ///
+    /// # Panics
+    ///
+    /// Panics if there are fewer than two samples along either axis.
+    pub fn from_fn(
+        origin: Point3,
+        size: (f64, f64),
+        resolution: (usize, usize),
+        f: impl Fn(f64, f64) -> f64,
+        mat: Material,
+    ) -> Self {
+        let (nx, nz) = resolution;
+        assert!(nx >= 2 && nz >= 2, "Heightfield needs at least 2x2 samples");
+        let mut heights = Vec::with_capacity(nx * nz);
+        for j in 0..nz {
+            for i in 0..nx {
+                #[allow(clippy::cast_precision_loss)]
+                let (u, v) = (i as f64 / (nx - 1) as f64, j as f64 / (nz - 1) as f64);
+                heights.push(f(
+                    size.0.mul_add(u, origin.x()),
+                    size.1.mul_add(v, origin.z()),
+                ));
+            }
+        }
+        Self::new(origin, size, resolution, heights, mat)
+    }
+
+    /// Load a grayscale height map, with black at height zero and white at `height_scale`.
+    /// Each pixel becomes one sample.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the image cannot be read or decoded, or is smaller
+    /// than 2x2 pixels.
+    pub fn from_image(
+        path: impl AsRef<Path>,
+        origin: Point3,
+        size: (f64, f64),
+        height_scale: f64,
+        mat: Material,
+    ) -> Result<Self, ImageError> {
+        let image = image::open(path)?.into_luma16();
+        if image.width() < 2 || image.height() < 2 {
+            return Err(ImageError::Parameter(ParameterError::from_kind(
+                ParameterErrorKind::Generic("height map needs at least 2x2 pixels".to_owned()),
+            )));
+        }
+        let resolution = (image.width() as usize, image.height() as usize);
+        let heights = image
+            .pixels()
+            .map(|pixel| f64::from(pixel.0[0]) / f64::from(u16::MAX) * height_scale)
+            .collect();
+        Ok(Self::new(origin, size, resolution, heights, mat))
+    }
+
+    fn height(&self, i: usize, j: usize) -> f64 {
+        self.heights[j * self.resolution.0 + i]
+    }
+
+    fn vertex(&self, i: usize, j: usize) -> Point3 {
+        #[allow(clippy::cast_precision_loss)]
+        Point3::new(
+            self.cell_size.0.mul_add(i as f64, self.origin.x()),
+            self.origin.y() + self.height(i, j),
+            self.cell_size.1.mul_add(j as f64, self.origin.z()),
+        )
+    }
+
+    /// Normals from central differences of the heights, one-sided at the edges
+    fn vertex_normals(&self) -> Vec<Vec3> {
+        let (nx, nz) = self.resolution;
+        let mut normals = Vec::with_capacity(nx * nz);
+        for j in 0..nz {
+            for i in 0..nx {
+                let (i0, i1) = (i.saturating_sub(1), (i + 1).min(nx - 1));
+                let (j0, j1) = (j.saturating_sub(1), (j + 1).min(nz - 1));
+                #[allow(clippy::cast_precision_loss)]
+                let slope_x = (self.height(i1, j) - self.height(i0, j))
+                    / ((i1 - i0) as f64 * self.cell_size.0);
+                #[allow(clippy::cast_precision_loss)]
+                let slope_z = (self.height(i, j1) - self.height(i, j0))
+                    / ((j1 - j0) as f64 * self.cell_size.1);
+                normals.push(Vec3::new(-slope_x, 1., -slope_z).unit());
+            }
+        }
+        normals
+    }
+
+    fn min_max_levels(&self) -> Vec<MinMaxLevel> {
+        let (width, depth) = (self.resolution.0 - 1, self.resolution.1 - 1);
+        let mut min = Vec::with_capacity(width * depth);
+        let mut max = Vec::with_capacity(width * depth);
+        for j in 0..depth {
+            for i in 0..width {
+                let corners = [
+                    self.height(i, j),
+                    self.height(i + 1, j),
+                    self.height(i, j + 1),
+                    self.height(i + 1, j + 1),
+                ];
+                min.push(corners.iter().copied().fold(f64::INFINITY, f64::min));
+                max.push(corners.iter().copied().fold(f64::NEG_INFINITY, f64::max));
+            }
+        }
+
+        let mut levels = vec![MinMaxLevel {
+            width,
+            depth,
+            min,
+            max,
+        }];
+        while levels[levels.len() - 1].width > 1 || levels[levels.len() - 1].depth > 1 {
+            let coarser = MinMaxLevel::coarsen(&levels[levels.len() - 1]);
+            levels.push(coarser);
+        }
+        levels
+    }
+
+    /// Walks the cells of `level` within the given index ranges in the order
+    /// the ray crosses them, during the parameter window `window`
+    fn walk(
+        &self,
+        ray: &Ray,
+        ray_t: &Interval,
+        level: usize,
+        (i_range, j_range): ((usize, usize), (usize, usize)),
+        window: &Interval,
+    ) -> Option<HitRecord> {
+        let cells = &self.levels[level];
+        let scale = f64::from(1 << level);
+        let size_x = self.cell_size.0 * scale;
+        let size_z = self.cell_size.1 * scale;
+
+        let origin = ray.origin();
+        let dir = ray.direction();
+        let start = ray.at(window.min());
+
+        // Cell containing the start of the window, clamped against rounding at its edges
+        let cell_index = |coord: f64, base: f64, size: f64, (low, high): (usize, usize)| {
+            let index = ((coord - base) / size).floor();
+            #[allow(clippy::cast_sign_loss, clippy::cast_possible_truncation)]
+            let index = index.max(0.) as usize;
+            index.clamp(low, high - 1)
+        };
+        let mut i = cell_index(start.x(), self.origin.x(), size_x, i_range);
+        let mut j = cell_index(start.z(), self.origin.z(), size_z, j_range);
+
+        // Ray parameters of the next cell boundaries, and of the spacing between them
+        #[allow(clippy::cast_precision_loss)]
+        let next_boundary = |index: usize, base: f64, size: f64, o: f64, d: f64| {
+            if d > 0. {
+                (size.mul_add((index + 1) as f64, base) - o) / d
+            } else if d < 0. {
+                (size.mul_add(index as f64, base) - o) / d
+            } else {
+                f64::INFINITY
+            }
+        };
+        let mut t_next_x = next_boundary(i, self.origin.x(), size_x, origin.x(), dir.x());
+        let mut t_next_z = next_boundary(j, self.origin.z(), size_z, origin.z(), dir.z());
+        let t_delta_x = (size_x / dir.x()).abs();
+        let t_delta_z = (size_z / dir.z()).abs();
+
+        let mut t_enter = window.min();
+        loop {
+            let t_exit = t_next_x.min(t_next_z).min(window.max());
+
+            // Only look inside if the ray's height over this cell overlaps the cell's heights
+            let (y_enter, y_exit) = (ray.at(t_enter).y(), ray.at(t_exit).y());
+            let cell = j * cells.width + i;
+            let low = self.origin.y() + cells.min[cell];
+            let high = self.origin.y() + cells.max[cell];
+            let slack = 1e-9 * (high - low).abs().max(1.);
+            if y_enter.min(y_exit) <= high + slack && y_enter.max(y_exit) >= low - slack {
+                let hit_record = if level == 0 {
+                    self.hit_cell(ray, ray_t, i, j)
+                } else {
+                    let finer = &self.levels[level - 1];
+                    let children = (
+                        (2 * i, (2 * i + 2).min(finer.width)),
+                        (2 * j, (2 * j + 2).min(finer.depth)),
+                    );
+                    self.walk(
+                        ray,
+                        ray_t,
+                        level - 1,
+                        children,
+                        &Interval::new(t_enter, t_exit),
+                    )
+                };
+                if hit_record.is_some() {
+                    return hit_record;
+                }
+            }
+
+            if t_exit >= window.max() {
+                return None;
+            }
+
+            // Step into the neighbouring cell across the nearer boundary
+            if t_next_x < t_next_z {
+                if dir.x() > 0. {
+                    i += 1;
+                } else {
+                    i = i.checked_sub(1)?;
+                }
+                t_enter = t_next_x;
+                t_next_x += t_delta_x;
+                if i >= i_range.1 || i < i_range.0 {
+                    return None;
+                }
+            } else {
+                if dir.z() > 0. {
+                    j += 1;
+                } else {
+                    j = j.checked_sub(1)?;
+                }
+                t_enter = t_next_z;
+                t_next_z += t_delta_z;
+                if j >= j_range.1 || j < j_range.0 {
+                    return None;
+                }
+            }
+        }
+    }
+
+    /// Intersects the two triangles of the base grid cell (i, j)
+    fn hit_cell(&self, ray: &Ray, ray_t: &Interval, i: usize, j: usize) -> Option<HitRecord> {
+        let corners = [(i, j), (i, j + 1), (i + 1, j + 1), (i + 1, j)];
+        let triangles = [
+            [corners[0], corners[1], corners[2]],
+            [corners[0], corners[2], corners[3]],
+        ];
+
+        let mut closest: Option<HitRecord> = None;
+        for triangle in triangles {
+            let [v0, v1, v2] = triangle.map(|(i, j)| self.vertex(i, j));
+            let t_max = closest.as_ref().map_or_else(|| ray_t.max(), HitRecord::t);
+            let Some((t, b0, b1, b2)) =
+                intersect_watertight(ray, &Interval::new(ray_t.min(), t_max), &v0, &v1, &v2)
+            else {
+                continue;
+            };
+
+            let [n0, n1, n2] = triangle.map(|(i, j)| &self.normals[j * self.resolution.0 + i]);
+            let shading_normal = b0 * n0 + b1 * n1 + b2 * n2;
+
+            #[allow(clippy::cast_precision_loss)]
+            let [uv0, uv1, uv2] = triangle.map(|(i, j)| {
+                (
+                    i as f64 / (self.resolution.0 - 1) as f64,
+                    j as f64 / (self.resolution.1 - 1) as f64,
+                )
+            });
+            let uv = (
+                b0.mul_add(uv0.0, b1.mul_add(uv1.0, b2 * uv2.0)),
+                b0.mul_add(uv0.1, b1.mul_add(uv1.1, b2 * uv2.1)),
+            );
+
+            closest = Some(triangle_hit_record(
+                ray,
+                t,
+                [&v0, &v1, &v2],
+                (b1, b2),
+                Some(shading_normal),
+                Some(uv),
+                self.mat.clone(),
+            ));
+        }
+
+        closest
+    }
+}
+
+impl Hittable for Heightfield {
+    fn hit(&self, ray: &Ray, ray_t: &Interval) -> Option<HitRecord> {
+        let window = self.bbox.hit_interval(ray, ray_t)?;
+        let top = self.levels.len() - 1;
+        self.walk(ray, ray_t, top, ((0, 1), (0, 1)), &window)
+    }
+
+    fn bounding_box(&self) -> &Aabb {
+        &self.bbox
+    }
+}
@@ -1,23 +1,37 @@
+use crate::aabb::Aabb;
 use crate::interval::Interval;
+use crate::mat4::Mat4;
 use crate::material::Material;
 use crate::ray::Ray;
 use crate::vec3::{Point3, Vec3};
 
+/// Relative distance to advance past a crossing when searching for the next one
+const CROSSING_EPSILON: f64 = 1e-9;
+
 pub struct HitRecord {
     p: Point3,
-    normal: Vec3,
+    normal: Vec3,           // Shading normal, facing against the ray
+    geometric_normal: Vec3, // True surface normal, facing against the ray
     mat: Material,
     t: f64,
+    u: f64, // Surface coordinates of the hit point
+    v: f64,
+    barycentric: (f64, f64), // Weights of the second and third triangle vertices
     front_face: bool,
 }
 
 impl HitRecord {
-    pub const fn new(p: Point3, normal: Vec3, mat: Material, t: f64) -> Self {
+    #[must_use]
+    pub fn new(p: Point3, normal: Vec3, mat: Material, t: f64) -> Self {
         Self {
             p,
-            normal,
+            normal: normal.clone(),
+            geometric_normal: normal,
             mat,
             t,
+            u: 0.,
+            v: 0.,
+            barycentric: (0., 0.),
             front_face: false,
         }
     }
@@ -32,34 +46,182 @@ impl HitRecord {
         // TODO: Can we enforce this with the type system?
 
         self.front_face = Self::is_front_face(ray, &outward_normal);
-        self.normal = if self.front_face {
+        self.geometric_normal = if self.front_face {
             outward_normal
         } else {
             -outward_normal
         };
+        self.normal = self.geometric_normal.clone();
+    }
+
+    /// Replaces the shading normal with an interpolated one, flipped onto the
+    /// same side as the geometric normal. Must be called after `set_face_normal`.
+    pub(crate) fn set_shading_normal(&mut self, outward_normal: Vec3) {
+        self.normal = if outward_normal.dot(&self.geometric_normal) < 0. {
+            -outward_normal
+        } else {
+            outward_normal
+        };
+    }
+
+    /// Moves the hit point and normals into another space. Normals go through
+    /// `normal_matrix`, the inverse transpose of `transform`, so they stay
+    /// perpendicular to the surface under non-uniform scaling.
+    pub(crate) fn transform(&mut self, transform: &Mat4, normal_matrix: &Mat4) {
+        self.p = transform.transform_point(&self.p);
+        self.normal = normal_matrix.transform_vector(&self.normal).unit();
+        self.geometric_normal = normal_matrix
+            .transform_vector(&self.geometric_normal)
+            .unit();
     }
 
+    /// Overrides which side of a surface was hit, for composite objects whose
+    /// inside differs from that of the primitive that was hit
+    pub(crate) const fn set_front_face(&mut self, front_face: bool) {
+        self.front_face = front_face;
+    }
+
+    pub(crate) const fn set_uv(&mut self, u: f64, v: f64) {
+        self.u = u;
+        self.v = v;
+    }
+
+    pub(crate) const fn set_barycentric(&mut self, b1: f64, b2: f64) {
+        self.barycentric = (b1, b2);
+    }
+
+    #[must_use]
     pub const fn normal(&self) -> &Vec3 {
         &self.normal
     }
 
+    #[must_use]
+    pub const fn geometric_normal(&self) -> &Vec3 {
+        &self.geometric_normal
+    }
+
+    /// Returns the barycentric coordinates of the hit point, as weights of
+    /// the three triangle vertices. Non-triangle primitives report `(1, 0, 0)`.
+    #[must_use]
+    pub fn barycentric(&self) -> (f64, f64, f64) {
+        let (b1, b2) = self.barycentric;
+        (1. - b1 - b2, b1, b2)
+    }
+
+    #[must_use]
     pub const fn t(&self) -> f64 {
         self.t
     }
 
+    #[must_use]
+    pub const fn u(&self) -> f64 {
+        self.u
+    }
+
+    #[must_use]
+    pub const fn v(&self) -> f64 {
+        self.v
+    }
+
+    #[must_use]
     pub const fn p(&self) -> &Point3 {
         &self.p
     }
 
+    #[must_use]
     pub const fn material(&self) -> &Material {
         &self.mat
     }
 
+    #[must_use]
     pub const fn front_face(&self) -> bool {
         self.front_face
     }
 }
 
+/// What `Hittable::hit_traced` finds out besides the hit itself
+#[derive(Default)]
+pub struct HitTrace {
+    pub(crate) node_visits: u64,
+    pub(crate) object: usize,
+}
+
+impl HitTrace {
+    /// Number of bounding volume hierarchy nodes whose boxes were tested
+    #[must_use]
+    pub const fn node_visits(&self) -> u64 {
+        self.node_visits
+    }
+
+    /// Position at which the hit object was added to the world. For objects
+    /// nested in other lists, this is the position of the outermost one.
+    #[must_use]
+    pub const fn object(&self) -> usize {
+        self.object
+    }
+}
+
 pub trait Hittable: Send + Sync {
     fn hit(&self, ray: &Ray, ray_t: &Interval) -> Option<HitRecord>;
+
+    /// Returns a box enclosing everything this object can be hit on
+    fn bounding_box(&self) -> &Aabb;
+
+    /// Whether the ray hits anything at all within `ray_t`, as for shadow and
+    /// occlusion rays. Unlike `hit`, this can stop at the first crossing found
+    /// rather than searching for the closest, and needs no hit record.
+    fn occluded(&self, ray: &Ray, ray_t: &Interval) -> bool {
+        self.hit(ray, ray_t).is_some()
+    }
+
+    /// Closest hit like `hit`, also recording in `trace` which object was hit
+    /// and how many bounding volume hierarchy nodes were tested, for debug
+    /// views. Kept apart from `hit` so rendering doesn't pay for the
+    /// bookkeeping. Only objects that hold others need to override it.
+    fn hit_traced(&self, ray: &Ray, ray_t: &Interval, _trace: &mut HitTrace) -> Option<HitRecord> {
+        self.hit(ray, ray_t)
+    }
+
+    /// Returns every point where the ray crosses the surface within `ray_t`,
+    /// ordered by `t`. For closed objects, the crossings alternate between
+    /// entering (front face) and leaving the inside, so consecutive pairs give
+    /// the intervals of the ray that lie within the object.
+    ///
+    /// The default implementation repeatedly asks `hit` for the next crossing.
+    fn hit_all(&self, ray: &Ray, ray_t: &Interval) -> Vec<HitRecord> {
+        let mut crossings = Vec::new();
+        let mut t_min = ray_t.min();
+        while let Some(hit_record) = self.hit(ray, &Interval::new(t_min, ray_t.max())) {
+            // Step just past this crossing so coincident surfaces aren't found forever
+            t_min = CROSSING_EPSILON.mul_add(hit_record.t().abs().max(1.), hit_record.t());
+            crossings.push(hit_record);
+        }
+        crossings
+    }
+
+    /// Picks a direction from `origin` towards a random point on the surface as
+    /// it is at `time`, so that emissive objects can be sampled as lights.
+    /// Returns the direction and its probability density over solid angle, or
+    /// `None` if the object can't be sampled.
+    fn sample(&self, _origin: &Point3, _time: f64) -> Option<(Vec3, f64)> {
+        None
+    }
+
+    /// Probability density over solid angle with which `sample` picks `direction`
+    /// from `origin` at `time`
+    fn pdf(&self, _origin: &Point3, _direction: &Vec3, _time: f64) -> f64 {
+        0.
+    }
+}
+
+/// Density over solid angle, seen from the origin of `ray`, of uniformly
+/// sampling a flat surface of `area` that the ray hits at `hit_record`
+pub(crate) fn area_to_solid_angle_pdf(ray: &Ray, hit_record: &HitRecord, area: f64) -> f64 {
+    let length_squared = ray.direction().length_squared();
+    let distance_squared = hit_record.t().powi(2) * length_squared;
+    let cosine = ray.direction().dot(hit_record.geometric_normal()).abs() / length_squared.sqrt();
+    if cosine < 1e-12 {
+        return 0.;
+    }
+    distance_squared / (cosine * area)
 }
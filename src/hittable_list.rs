@@ -1,24 +1,67 @@
-use crate::hittable::{HitRecord, Hittable};
+use rand::Rng;
+
+use crate::aabb::Aabb;
+use crate::hittable::{HitRecord, HitTrace, Hittable};
 use crate::interval::Interval;
 use crate::ray::Ray;
+use crate::vec3::{Point3, Vec3};
 
 pub struct HittableList {
     objects: Vec<Box<dyn Hittable>>,
+    indices: Vec<usize>, // Position of each object reported by `hit_traced`
+    bbox: Aabb,
 }
 
 impl HittableList {
-    pub fn default() -> Self {
+    #[must_use]
+    pub const fn default() -> Self {
         Self {
             objects: Vec::new(),
+            indices: Vec::new(),
+            bbox: Aabb::empty(),
         }
     }
 
     pub fn clear(&mut self) {
         self.objects.clear();
+        self.indices.clear();
+        self.bbox = Aabb::empty();
     }
 
     pub fn add(&mut self, object: Box<dyn Hittable>) {
+        self.add_indexed(self.objects.len(), object);
+    }
+
+    /// Adds an object that `hit_traced` reports at position `index`, for
+    /// containers that regroup the objects of another list
+    pub(crate) fn add_indexed(&mut self, index: usize, object: Box<dyn Hittable>) {
+        self.bbox = Aabb::union(&self.bbox, object.bounding_box());
         self.objects.push(object);
+        self.indices.push(index);
+    }
+
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.objects.len()
+    }
+
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.objects.is_empty()
+    }
+
+    /// Returns the objects whose bounding boxes overlap `region`
+    pub fn overlapping(&self, region: &Aabb) -> Vec<&dyn Hittable> {
+        self.objects
+            .iter()
+            .filter(|object| object.bounding_box().overlaps(region))
+            .map(AsRef::as_ref)
+            .collect()
+    }
+
+    /// Consumes the list, handing back its objects along with their positions
+    pub(crate) fn into_indexed_objects(self) -> Vec<(usize, Box<dyn Hittable>)> {
+        self.indices.into_iter().zip(self.objects).collect()
     }
 }
 
@@ -35,4 +78,56 @@ impl Hittable for HittableList {
 
         maybe_record
     }
+
+    fn hit_traced(&self, ray: &Ray, ray_t: &Interval, trace: &mut HitTrace) -> Option<HitRecord> {
+        let mut closest_so_far = ray_t.max();
+        let mut maybe_record: Option<HitRecord> = None;
+        for (object, index) in self.objects.iter().zip(&self.indices) {
+            if let Some(hit_record) =
+                object.hit_traced(ray, &Interval::new(ray_t.min(), closest_so_far), trace)
+            {
+                closest_so_far = hit_record.t();
+                // Set after any nested list, so the outermost position wins
+                trace.object = *index;
+                maybe_record = Some(hit_record);
+            }
+        }
+
+        maybe_record
+    }
+
+    fn bounding_box(&self) -> &Aabb {
+        &self.bbox
+    }
+
+    fn occluded(&self, ray: &Ray, ray_t: &Interval) -> bool {
+        self.objects
+            .iter()
+            .any(|object| object.occluded(ray, ray_t))
+    }
+
+    /// Samples one of the objects, chosen uniformly at random. The density
+    /// accounts for every object, as the direction may also point at others.
+    fn sample(&self, origin: &Point3, time: f64) -> Option<(Vec3, f64)> {
+        if self.objects.is_empty() {
+            return None;
+        }
+        let index = rand::thread_rng().gen_range(0..self.objects.len());
+        let (direction, _) = self.objects[index].sample(origin, time)?;
+        let pdf = self.pdf(origin, &direction, time);
+        Some((direction, pdf))
+    }
+
+    #[allow(clippy::cast_precision_loss)]
+    fn pdf(&self, origin: &Point3, direction: &Vec3, time: f64) -> f64 {
+        if self.objects.is_empty() {
+            return 0.;
+        }
+        let total: f64 = self
+            .objects
+            .iter()
+            .map(|object| object.pdf(origin, direction, time))
+            .sum();
+        total / self.objects.len() as f64
+    }
 }
@@ -0,0 +1,150 @@
+use std::path::Path;
+
+use image::{ColorType, ImageError};
+
+use crate::color::srgb_to_linear;
+use crate::texture::Texture;
+use crate::vec3::{Color, Point3};
+
+/// Load an image as its width, height and linear colors row by row from the
+/// top. Floating point images such as HDR and EXR are taken to be linear
+/// already, while integer images are decoded from sRGB.
+pub(crate) fn load_linear(
+    path: impl AsRef<Path>,
+) -> Result<(usize, usize, Vec<Color>), ImageError> {
+    let image = image::open(path)?;
+    let is_linear = matches!(image.color(), ColorType::Rgb32F | ColorType::Rgba32F);
+
+    let image = image.into_rgb32f();
+    let (width, height) = (image.width() as usize, image.height() as usize);
+    let pixels = image
+        .pixels()
+        .map(|pixel| {
+            let [r, g, b] = pixel.0.map(f64::from);
+            if is_linear {
+                Color::new(r, g, b)
+            } else {
+                Color::new(srgb_to_linear(r), srgb_to_linear(g), srgb_to_linear(b))
+            }
+        })
+        .collect();
+
+    Ok((width, height, pixels))
+}
+
+/// How texture coordinates outside of [0, 1] are mapped back onto the image
+#[derive(Clone, Copy)]
+pub enum WrapMode {
+    /// Tile the image
+    Repeat,
+    /// Stretch the edge pixels outwards
+    Clamp,
+}
+
+/// How the colors of neighbouring pixels are combined
+#[derive(Clone, Copy)]
+pub enum Filter {
+    Nearest,
+    Bilinear,
+}
+
+/// Texture mapped from an image over the unit square of `(u, v)`, with `v`
+/// running from the bottom row of the image to the top. Pixels are stored as
+/// linear colors.
+pub struct ImageTexture {
+    width: usize,
+    height: usize,
+    pixels: Vec<Color>,
+    wrap: WrapMode,
+    filter: Filter,
+}
+
+impl ImageTexture {
+    /// Create a texture from linear colors, stored row by row from the top
+    ///
+    /// # Panics
+    ///
+    /// Panics if the image is empty or the number of pixels doesn't match its size.
+    #[must_use]
+    pub fn new(width: usize, height: usize, pixels: Vec<Color>) -> Self {
+        assert!(width > 0 && height > 0, "Image texture cannot be empty");
+        assert_eq!(
+            pixels.len(),
+            width * height,
+            "Image texture needs one color per pixel"
+        );
+
+        Self {
+            width,
+            height,
+            pixels,
+            wrap: WrapMode::Repeat,
+            filter: Filter::Bilinear,
+        }
+    }
+
+    /// Load a PNG, JPEG, HDR or any other image format supported by the `image`
+    /// crate. Floating point images such as HDR are taken to be linear already,
+    /// while integer images are decoded from sRGB.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the image cannot be read or decoded.
+    pub fn open(path: impl AsRef<Path>) -> Result<Self, ImageError> {
+        let (width, height, pixels) = load_linear(path)?;
+        Ok(Self::new(width, height, pixels))
+    }
+
+    #[must_use]
+    pub const fn wrap(mut self, wrap: WrapMode) -> Self {
+        self.wrap = wrap;
+        self
+    }
+
+    #[must_use]
+    pub const fn filter(mut self, filter: Filter) -> Self {
+        self.filter = filter;
+        self
+    }
+
+    /// Color of the pixel at column `x` and row `y`, which may lie outside the image
+    #[allow(
+        clippy::cast_possible_truncation,
+        clippy::cast_possible_wrap,
+        clippy::cast_sign_loss
+    )]
+    fn pixel(&self, x: i64, y: i64) -> &Color {
+        let fold = |index: i64, size: usize| {
+            let size = size as i64;
+            let index = match self.wrap {
+                WrapMode::Repeat => index.rem_euclid(size),
+                WrapMode::Clamp => index.clamp(0, size - 1),
+            };
+            index as usize
+        };
+        &self.pixels[fold(y, self.height) * self.width + fold(x, self.width)]
+    }
+}
+
+impl Texture for ImageTexture {
+    #[allow(clippy::cast_precision_loss, clippy::cast_possible_truncation)]
+    fn value(&self, u: f64, v: f64, _p: &Point3) -> Color {
+        // Continuous pixel coordinates, with pixel centers at half-integers
+        let x = u * self.width as f64;
+        let y = (1. - v) * self.height as f64;
+
+        match self.filter {
+            Filter::Nearest => self.pixel(x.floor() as i64, y.floor() as i64).clone(),
+            Filter::Bilinear => {
+                let (x, y) = (x - 0.5, y - 0.5);
+                let (x0, y0) = (x.floor(), y.floor());
+                let (tx, ty) = (x - x0, y - y0);
+                let (x0, y0) = (x0 as i64, y0 as i64);
+
+                let top = (1. - tx) * self.pixel(x0, y0) + tx * self.pixel(x0 + 1, y0);
+                let bottom = (1. - tx) * self.pixel(x0, y0 + 1) + tx * self.pixel(x0 + 1, y0 + 1);
+                (1. - ty) * top + ty * bottom
+            }
+        }
+    }
+}
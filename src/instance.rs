@@ -0,0 +1,191 @@
+use std::sync::Arc;
+
+use crate::aabb::Aabb;
+use crate::hittable::{HitRecord, HitTrace, Hittable};
+use crate::interval::Interval;
+use crate::mat4::Mat4;
+use crate::ray::Ray;
+use crate::vec3::{Point3, Vec3};
+
+/// Places a shared object in the world under an affine transform, without
+/// copying its geometry
+pub struct Instance {
+    object: Arc<dyn Hittable>,
+    transform: Mat4,     // Object space to world space
+    inverse: Mat4,       // World space to object space
+    normal_matrix: Mat4, // Inverse transpose of `transform`, for normals
+    volume_scale: f64,   // Absolute determinant of `transform`
+    bbox: Aabb,
+}
+
+impl Instance {
+    /// # Panics
+    ///
+    /// Panics if `transform` is not invertible, e.g. when it scales an axis by zero.
+    pub fn new(object: Arc<dyn Hittable>, transform: Mat4) -> Self {
+        let inverse = transform
+            .inverse()
+            .expect("Instance transform must be invertible");
+        let normal_matrix = inverse.transpose();
+        let volume_scale = transform.linear_determinant().abs();
+
+        let bbox = Self::transform_bbox(&transform, object.bounding_box());
+
+        Self {
+            object,
+            transform,
+            inverse,
+            normal_matrix,
+            volume_scale,
+            bbox,
+        }
+    }
+
+    /// World-space box enclosing `bbox` under `transform`, built one world axis
+    /// at a time from the extremes each object axis contributes (Arvo, 1990).
+    /// Object axes that a row doesn't mix in are skipped rather than multiplied
+    /// by zero, so unbounded objects such as planes give infinite bounds where
+    /// they extend and finite ones elsewhere, instead of NaN.
+    fn transform_bbox(transform: &Mat4, bbox: &Aabb) -> Aabb {
+        if bbox.is_empty() {
+            return Aabb::empty();
+        }
+
+        let world_axis = |row: usize| {
+            let offset = transform.get(row, 3);
+            let (mut min, mut max) = (offset, offset);
+            for col in 0..3 {
+                let factor = transform.get(row, col);
+                if factor == 0. {
+                    continue;
+                }
+                let interval = bbox.axis_interval(col);
+                let (a, b) = (factor * interval.min(), factor * interval.max());
+                min += a.min(b);
+                max += a.max(b);
+            }
+            Interval::new(min, max)
+        };
+        Aabb::new(world_axis(0), world_axis(1), world_axis(2))
+    }
+
+    /// Transform the ray into object space. The direction is not renormalized,
+    /// so the ray parameter t means the same thing in both spaces.
+    fn object_ray(&self, ray: &Ray) -> Ray {
+        Ray::new(
+            self.inverse.transform_point(ray.origin()),
+            self.inverse.transform_vector(ray.direction()),
+            ray.time(),
+        )
+    }
+}
+
+impl Hittable for Instance {
+    fn hit(&self, ray: &Ray, ray_t: &Interval) -> Option<HitRecord> {
+        let mut hit_record = self.object.hit(&self.object_ray(ray), ray_t)?;
+        hit_record.transform(&self.transform, &self.normal_matrix);
+
+        Some(hit_record)
+    }
+
+    fn bounding_box(&self) -> &Aabb {
+        &self.bbox
+    }
+
+    fn hit_traced(&self, ray: &Ray, ray_t: &Interval, trace: &mut HitTrace) -> Option<HitRecord> {
+        let mut hit_record = self
+            .object
+            .hit_traced(&self.object_ray(ray), ray_t, trace)?;
+        hit_record.transform(&self.transform, &self.normal_matrix);
+
+        Some(hit_record)
+    }
+
+    fn occluded(&self, ray: &Ray, ray_t: &Interval) -> bool {
+        self.object.occluded(&self.object_ray(ray), ray_t)
+    }
+
+    fn sample(&self, origin: &Point3, time: f64) -> Option<(Vec3, f64)> {
+        let (direction, _) = self
+            .object
+            .sample(&self.inverse.transform_point(origin), time)?;
+        let direction = self.transform.transform_vector(&direction);
+        let pdf = self.pdf(origin, &direction, time);
+        (pdf > 0.).then_some((direction, pdf))
+    }
+
+    /// The object's density, corrected for how the transform stretches solid
+    /// angle: a unit world direction `w` comes from the object direction along
+    /// `M⁻¹ w`, and densities shrink by `|det M| |M⁻¹ w|³` on the way out.
+    /// Rotations and uniform scales leave them unchanged.
+    fn pdf(&self, origin: &Point3, direction: &Vec3, time: f64) -> f64 {
+        let object_direction = self.inverse.transform_vector(&direction.unit());
+        let object_pdf = self.object.pdf(
+            &self.inverse.transform_point(origin),
+            &object_direction,
+            time,
+        );
+        object_pdf / (self.volume_scale * object_direction.length().powi(3))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
+
+    use super::*;
+    use crate::material::Material;
+    use crate::quad::Quad;
+    use crate::texture::SolidColor;
+    use crate::vec3::Color;
+
+    fn light() -> Material {
+        Material::DiffuseLight {
+            emit: Arc::new(SolidColor::new(Color::new(1., 1., 1.))),
+            intensity: 1.,
+        }
+    }
+
+    #[test]
+    fn samples_like_transformed_geometry() {
+        // A unit square stretched unevenly, turned and moved, against the same
+        // quad built directly in world space
+        let transform = &(&Mat4::translation(&Vec3::new(1., 2., -3.)) * &Mat4::rotation_y(30.))
+            * &Mat4::scaling(&Vec3::new(3., 0.5, 2.));
+        let instance = Instance::new(
+            Arc::new(Quad::new(
+                Point3::new(0., 0., 0.),
+                Vec3::new(1., 0., 0.),
+                Vec3::new(0., 1., 0.),
+                light(),
+            )),
+            transform.clone(),
+        );
+        let quad = Quad::new(
+            transform.transform_point(&Point3::new(0., 0., 0.)),
+            transform.transform_vector(&Vec3::new(1., 0., 0.)),
+            transform.transform_vector(&Vec3::new(0., 1., 0.)),
+            light(),
+        );
+
+        let mut rng = StdRng::seed_from_u64(5);
+        for _ in 0..100 {
+            let origin = Point3::new(
+                rng.gen_range(-10. ..10.),
+                rng.gen_range(-10. ..10.),
+                rng.gen_range(-10. ..10.),
+            );
+            let (direction, pdf) = instance
+                .sample(&origin, 0.)
+                .expect("the square is visible from both sides");
+            let expected = quad.pdf(&origin, &direction, 0.);
+            assert!(
+                (pdf - expected).abs() <= 1e-9 * expected,
+                "instance density {pdf}, world density {expected}"
+            );
+        }
+    }
+}
@@ -0,0 +1,363 @@
+use rand::Rng;
+
+use crate::background::Background;
+use crate::hittable::{HitRecord, HitTrace, Hittable};
+use crate::hittable_list::HittableList;
+use crate::interval::Interval;
+use crate::ray::Ray;
+use crate::vec3::{Color, Point3, Vec3};
+
+/// Everything a camera ray can run into: the objects, the subset of them to
+/// sample as lights, and what lies beyond
+pub struct Scene<'a> {
+    world: &'a dyn Hittable,
+    lights: &'a HittableList,
+    background: &'a dyn Background,
+    sample_background: bool, // Whether the background can be sampled as a light
+}
+
+impl<'a> Scene<'a> {
+    pub fn new(
+        world: &'a dyn Hittable,
+        lights: &'a HittableList,
+        background: &'a dyn Background,
+    ) -> Self {
+        Self {
+            world,
+            lights,
+            background,
+            sample_background: background.is_samplable(),
+        }
+    }
+
+    #[must_use]
+    pub fn world(&self) -> &dyn Hittable {
+        self.world
+    }
+
+    #[must_use]
+    pub const fn lights(&self) -> &HittableList {
+        self.lights
+    }
+
+    #[must_use]
+    pub fn background(&self) -> &dyn Background {
+        self.background
+    }
+
+    /// Closest hit along `ray`, skipping the first bit to avoid shadow acne
+    #[must_use]
+    pub fn hit(&self, ray: &Ray) -> Option<HitRecord> {
+        self.world.hit(ray, &Interval::new(0.001, f64::INFINITY))
+    }
+
+    /// Closest hit like `hit`, through the slower traversal that also traces
+    /// which object was hit and how many BVH nodes were tested
+    #[must_use]
+    pub fn hit_traced(&self, ray: &Ray) -> (Option<HitRecord>, HitTrace) {
+        let mut trace = HitTrace::default();
+        let hit_record =
+            self.world
+                .hit_traced(ray, &Interval::new(0.001, f64::INFINITY), &mut trace);
+        (hit_record, trace)
+    }
+
+    /// Whether anything lies along `ray` within `max_distance` times the length
+    /// of its direction, skipping the first bit like `hit`
+    #[must_use]
+    pub fn occluded(&self, ray: &Ray, max_distance: f64) -> bool {
+        self.world
+            .occluded(ray, &Interval::new(0.001, max_distance))
+    }
+
+    /// Next-event estimation: light reaching a hit straight from one sampled
+    /// light, or from the background, through a shadow ray. Weighed against the
+    /// chance that sampling the material would have found the same light.
+    #[must_use]
+    pub fn direct_light(&self, ray: &Ray, hit_record: &HitRecord) -> Color {
+        let no_light = Color::new(0., 0., 0.);
+        let strategies = self.lights.len() + usize::from(self.sample_background);
+        if strategies == 0 {
+            return no_light;
+        }
+
+        let sample = if rand::thread_rng().gen_range(0..strategies) < self.lights.len() {
+            self.lights.sample(hit_record.p(), ray.time())
+        } else {
+            self.background.sample()
+        };
+        let Some((direction, _)) = sample else {
+            return no_light;
+        };
+
+        let light_pdf = self.light_pdf(hit_record.p(), &direction, ray.time());
+        if light_pdf <= 0. {
+            return no_light;
+        }
+        let material = hit_record.material();
+        let response = material.eval(ray, hit_record, &direction);
+        let weight = power_heuristic(light_pdf, material.pdf(ray, hit_record, &direction));
+
+        // Whatever the shadow ray finds first is what lights the point
+        let shadow_ray = Ray::new(hit_record.p().clone(), direction, ray.time());
+        let radiance = self.hit(&shadow_ray).map_or_else(
+            || self.background.color(&shadow_ray.direction().unit()),
+            |light_hit| light_hit.material().emitted(&light_hit),
+        );
+        (weight / light_pdf) * (response * radiance)
+    }
+
+    /// Density over solid angle with which `direct_light` picks `direction`
+    /// from `origin` at `time`, choosing evenly between the lights and the background
+    #[allow(clippy::cast_precision_loss)]
+    #[must_use]
+    pub fn light_pdf(&self, origin: &Point3, direction: &Vec3, time: f64) -> f64 {
+        let light_count = self.lights.len() as f64;
+        let mut total = light_count * self.lights.pdf(origin, direction, time);
+        let mut strategies = light_count;
+        if self.sample_background {
+            total += self.background.pdf(&direction.unit());
+            strategies += 1.;
+        }
+
+        if strategies > 0. {
+            total / strategies
+        } else {
+            0.
+        }
+    }
+}
+
+/// Light transport algorithm, turning a camera ray into the color it sees.
+/// Quick one-off views can be written as closures over the ray and the scene.
+pub trait Integrator: Send + Sync {
+    /// Color seen along the camera `ray`
+    fn ray_color(&self, ray: &Ray, scene: &Scene) -> Color;
+
+    /// Whether `ray_color` returns linear colors, which are gamma encoded when
+    /// saved to 8-bit images. Other values, such as normals or distances, are
+    /// saved as is.
+    fn outputs_color(&self) -> bool {
+        true
+    }
+}
+
+impl<F> Integrator for F
+where
+    F: Fn(&Ray, &Scene) -> Color + Send + Sync,
+{
+    fn ray_color(&self, ray: &Ray, scene: &Scene) -> Color {
+        self(ray, scene)
+    }
+}
+
+/// Unidirectional path tracer, combining sampled lights and material bounces
+/// with multiple importance sampling
+///
+/// Paths are followed in a loop rather than by recursion. After `roulette_depth`
+/// bounces, Russian roulette ends paths at random, more often the less light they
+/// can still carry, and boosts the survivors to make up for the ones it ended.
+/// Deep paths then cost little, so `max_depth` can be raised freely.
+pub struct PathTracer {
+    max_depth: u32,      // Maximum number of ray bounces in scene
+    roulette_depth: u32, // Bounces before paths may be ended at random
+}
+
+impl PathTracer {
+    #[must_use]
+    pub const fn new(max_depth: u32) -> Self {
+        Self {
+            max_depth,
+            roulette_depth: 3,
+        }
+    }
+
+    /// Set how many bounces every path gets before Russian roulette may end it
+    #[must_use]
+    pub const fn roulette_depth(mut self, roulette_depth: u32) -> Self {
+        self.roulette_depth = roulette_depth;
+        self
+    }
+}
+
+impl Integrator for PathTracer {
+    fn ray_color(&self, ray: &Ray, scene: &Scene) -> Color {
+        let mut color = Color::new(0., 0., 0.);
+        let mut throughput = Color::new(1., 1., 1.); // Fraction of light the path carries back
+        let mut ray = ray.clone();
+
+        // Emission found by the ray is scaled by the multiple importance sampling
+        // weight of the bounce that produced it, since light sampling may have
+        // found the same light
+        let mut emission_weight = 1.;
+
+        for depth in 1..=self.max_depth {
+            let Some(hit_record) = scene.hit(&ray) else {
+                let background = scene.background().color(&ray.direction().unit());
+                color += emission_weight * (throughput * background);
+                break;
+            };
+
+            let material = hit_record.material();
+            color += emission_weight * (&throughput * material.emitted(&hit_record));
+
+            // Specular materials can't be lit directly, so only follow the bounce.
+            // Light sampling estimates the light in its own right, so it must not
+            // depend on whether the material's sample below succeeds.
+            let specular = material.is_specular();
+            if !specular {
+                color += &throughput * scene.direct_light(&ray, &hit_record);
+            }
+            let Some((attenuation, scattered)) = material.scatter(&ray, &hit_record) else {
+                break;
+            };
+
+            emission_weight = if specular {
+                1.
+            } else {
+                power_heuristic(
+                    material.pdf(&ray, &hit_record, scattered.direction()),
+                    scene.light_pdf(hit_record.p(), scattered.direction(), scattered.time()),
+                )
+            };
+            throughput = throughput * attenuation;
+
+            if depth >= self.roulette_depth {
+                let survival = throughput
+                    .x()
+                    .max(throughput.y())
+                    .max(throughput.z())
+                    .min(1.);
+                if rand::thread_rng().gen::<f64>() >= survival {
+                    break;
+                }
+                throughput /= survival;
+            }
+            ray = scattered;
+        }
+
+        color
+    }
+}
+
+/// Ambient occlusion, for clay renders and look development. Casts
+/// cosine-weighted rays from each camera hit and returns the fraction that
+/// escape without hitting anything within `max_distance`.
+///
+/// This is how white clay would look under a uniform white sky, so rays that
+/// miss the scene are white.
+pub struct AmbientOcclusion {
+    samples: u32,      // Occlusion rays cast from each camera hit
+    max_distance: f64, // Distance beyond which objects no longer occlude
+}
+
+impl AmbientOcclusion {
+    #[must_use]
+    pub const fn new(samples: u32, max_distance: f64) -> Self {
+        Self {
+            samples: if samples > 0 { samples } else { 1 },
+            max_distance,
+        }
+    }
+}
+
+impl Integrator for AmbientOcclusion {
+    #[allow(clippy::cast_precision_loss)]
+    fn ray_color(&self, ray: &Ray, scene: &Scene) -> Color {
+        let Some(hit_record) = scene.hit(ray) else {
+            return Color::new(1., 1., 1.);
+        };
+
+        let open = (0..self.samples)
+            .filter(|_| {
+                let mut direction = hit_record.normal() + Vec3::random_unit_vector();
+
+                // Catch degenerate directions
+                if direction.near_zero() {
+                    direction = hit_record.normal().clone();
+                }
+
+                let occlusion_ray = Ray::new(hit_record.p().clone(), direction.unit(), ray.time());
+                !scene.occluded(&occlusion_ray, self.max_distance)
+            })
+            .count();
+
+        let visibility = open as f64 / f64::from(self.samples);
+        Color::new(visibility, visibility, visibility)
+    }
+}
+
+/// Veach's power heuristic: the weight of a sample drawn with density `pdf`
+/// when another strategy could have drawn it with density `other_pdf`
+#[must_use]
+pub fn power_heuristic(pdf: f64, other_pdf: f64) -> f64 {
+    let pdf = pdf * pdf;
+    let other_pdf = other_pdf * other_pdf;
+    if pdf + other_pdf > 0. {
+        pdf / (pdf + other_pdf)
+    } else {
+        0.
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use super::*;
+    use crate::material::Material;
+    use crate::quad::Quad;
+    use crate::texture::SolidColor;
+
+    fn light() -> Quad {
+        Quad::new(
+            Point3::new(-3., 2., -3.),
+            Vec3::new(6., 0., 0.),
+            Vec3::new(0., 0., 6.),
+            Material::DiffuseLight {
+                emit: Arc::new(SolidColor::new(Color::new(1., 1., 1.))),
+                intensity: 1.,
+            },
+        )
+    }
+
+    /// Average brightness seen along `ray`, with or without sampling the light
+    fn brightness(world: &HittableList, lights: &HittableList, ray: &Ray) -> f64 {
+        let background = |_: &Vec3| Color::new(0., 0., 0.);
+        let scene = Scene::new(world, lights, &background);
+        let integrator = PathTracer::new(2);
+        let samples = 20_000;
+        let total: f64 = (0..samples)
+            .map(|_| integrator.ray_color(ray, &scene).x())
+            .sum();
+        total / f64::from(samples)
+    }
+
+    #[test]
+    fn light_sampling_matches_material_sampling_on_rough_metal() {
+        // A rough metal floor seen at a grazing angle under a large light. Its
+        // reflections often point below the surface, which must not stop the
+        // light from being sampled.
+        let mut world = HittableList::default();
+        world.add(Box::new(Quad::new(
+            Point3::new(-10., 0., -10.),
+            Vec3::new(20., 0., 0.),
+            Vec3::new(0., 0., 20.),
+            Material::Metal {
+                albedo: Arc::new(SolidColor::new(Color::new(0.8, 0.8, 0.8))),
+                fuzz: 0.9,
+            },
+        )));
+        world.add(Box::new(light()));
+        let mut lights = HittableList::default();
+        lights.add(Box::new(light()));
+
+        let ray = Ray::new(Point3::new(-5., 1., 0.), Vec3::new(1., -0.2, 0.), 0.);
+        let with_lights = brightness(&world, &lights, &ray);
+        let material_only = brightness(&world, &HittableList::default(), &ray);
+        assert!(
+            (with_lights - material_only).abs() < 0.05 * material_only,
+            "with light sampling {with_lights}, without {material_only}"
+        );
+    }
+}
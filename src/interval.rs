@@ -1,9 +1,11 @@
+#[derive(Clone)]
 pub struct Interval {
     min: f64,
     max: f64,
 }
 
 impl Interval {
+    #[must_use]
     pub const fn default() -> Self {
         Self {
             min: f64::INFINITY,
@@ -11,14 +13,26 @@ impl Interval {
         }
     }
 
+    #[must_use]
     pub const fn new(min: f64, max: f64) -> Self {
         Self { min, max }
     }
 
+    /// Returns the tightest interval enclosing both `a` and `b`
+    #[must_use]
+    pub const fn union(a: &Self, b: &Self) -> Self {
+        Self {
+            min: a.min.min(b.min),
+            max: a.max.max(b.max),
+        }
+    }
+
+    #[must_use]
     pub const fn empty() -> Self {
         Self::default()
     }
 
+    #[must_use]
     pub const fn universe() -> Self {
         Self {
             min: f64::NEG_INFINITY,
@@ -26,27 +40,46 @@ impl Interval {
         }
     }
 
+    #[must_use]
     pub const fn min(&self) -> f64 {
         self.min
     }
 
+    #[must_use]
     pub const fn max(&self) -> f64 {
         self.max
     }
 
+    #[must_use]
     pub fn size(&self) -> f64 {
         self.max - self.min
     }
 
+    #[must_use]
     pub fn contains(&self, x: f64) -> bool {
         self.min <= x && x <= self.max
     }
 
+    #[must_use]
     pub fn surrounds(&self, x: f64) -> bool {
         self.min < x && x < self.max
     }
 
-    pub fn clamp(&self, x: f64) -> f64 {
+    #[must_use]
+    pub const fn clamp(&self, x: f64) -> f64 {
         x.clamp(self.min, self.max)
     }
+
+    /// Returns true if the two intervals share at least one point
+    #[must_use]
+    pub fn overlaps(&self, other: &Self) -> bool {
+        self.min <= other.max && other.min <= self.max
+    }
+
+    /// Returns the interval grown by `delta`, split evenly between both ends
+    #[must_use]
+    pub fn expand(&self, delta: f64) -> Self {
+        let padding = delta / 2.;
+        Self::new(self.min - padding, self.max + padding)
+    }
 }
@@ -0,0 +1,36 @@
+pub mod aabb;
+pub mod background;
+pub mod bvh;
+pub mod camera;
+pub mod color;
+pub mod cone;
+pub mod constant_medium;
+pub mod csg;
+pub mod cylinder;
+pub mod debug;
+pub mod disk;
+pub mod environment;
+pub mod heightfield;
+pub mod hittable;
+pub mod hittable_list;
+pub mod image_texture;
+pub mod instance;
+pub mod integrator;
+pub mod interval;
+pub mod mat4;
+pub mod material;
+pub mod mesh;
+pub mod noise;
+pub mod obj;
+pub mod onb;
+pub mod plane;
+pub mod polynomial;
+pub mod quad;
+pub mod ray;
+pub mod sdf;
+pub mod sky;
+pub mod sphere;
+pub mod texture;
+pub mod torus;
+pub mod triangle;
+pub mod vec3;
@@ -1,29 +1,25 @@
-use rand::{Rng, thread_rng};
+use std::sync::Arc;
 
-use crate::camera::Camera;
-use crate::hittable_list::HittableList;
-use crate::material::Material::{Dielectric, Lambertian, Metal};
-use crate::sphere::Sphere;
-use crate::vec3::{Color, Point3, Vec3};
+use rand::{Rng, thread_rng};
 
-mod camera;
-mod color;
-mod hittable;
-mod hittable_list;
-mod interval;
-mod material;
-mod ray;
-mod sphere;
-mod vec3;
+use raytracing_in_one_weekend::bvh::BvhNode;
+use raytracing_in_one_weekend::camera::Camera;
+use raytracing_in_one_weekend::hittable_list::HittableList;
+use raytracing_in_one_weekend::material::Material::{Dielectric, Lambertian, Metal};
+use raytracing_in_one_weekend::quad::Quad;
+use raytracing_in_one_weekend::sphere::Sphere;
+use raytracing_in_one_weekend::texture::SolidColor;
+use raytracing_in_one_weekend::vec3::{Color, Point3, Vec3};
 
 fn main() {
     let mut world = HittableList::default();
 
-    world.add(Box::new(Sphere::new(
-        Point3::new(0., -1000., 0.),
-        1000.,
+    world.add(Box::new(Quad::new(
+        Point3::new(-1000., 0., -1000.),
+        Vec3::new(0., 0., 2000.),
+        Vec3::new(2000., 0., 0.),
         Lambertian {
-            albedo: Color::new(0.5, 0.5, 0.5),
+            albedo: Arc::new(SolidColor::new(Color::new(0.5, 0.5, 0.5))),
         },
     )));
 
@@ -43,13 +39,13 @@ fn main() {
             match choose_mat {
                 ..0.8 => {
                     // diffuse
-                    let albedo = Color::random() * Color::random();
+                    let albedo = Arc::new(SolidColor::new(Color::random() * Color::random()));
                     let sphere_material = Lambertian { albedo };
                     world.add(Box::new(Sphere::new(center.clone(), 0.2, sphere_material)));
                 }
                 0.8..0.95 => {
                     // metal
-                    let albedo = Color::random_in_interval(0.5, 1.);
+                    let albedo = Arc::new(SolidColor::new(Color::random_in_interval(0.5, 1.)));
                     let fuzz = rng.gen_range(0.0..0.5);
                     let sphere_material = Metal { albedo, fuzz };
                     world.add(Box::new(Sphere::new(center.clone(), 0.2, sphere_material)));
@@ -58,7 +54,7 @@ fn main() {
                     // glass
                     let sphere_material = Dielectric {
                         refraction_index: 1.5,
-                        color: Color::new(1., 1., 1.),
+                        color: Arc::new(SolidColor::new(Color::new(1., 1., 1.))),
                     };
                     world.add(Box::new(Sphere::new(center.clone(), 0.2, sphere_material)));
                 }
@@ -68,7 +64,7 @@ fn main() {
 
     let material_1 = Dielectric {
         refraction_index: 1.5,
-        color: Color::new(1., 1., 1.),
+        color: Arc::new(SolidColor::new(Color::new(1., 1., 1.))),
     };
     world.add(Box::new(Sphere::new(
         Point3::new(0., 1., 0.),
@@ -77,7 +73,7 @@ fn main() {
     )));
 
     let material_2 = Lambertian {
-        albedo: Color::new(0.4, 0.2, 0.1),
+        albedo: Arc::new(SolidColor::new(Color::new(0.4, 0.2, 0.1))),
     };
     world.add(Box::new(Sphere::new(
         Point3::new(-4., 1., 0.),
@@ -86,7 +82,7 @@ fn main() {
     )));
 
     let material_3 = Metal {
-        albedo: Color::new(0.7, 0.6, 0.5),
+        albedo: Arc::new(SolidColor::new(Color::new(0.7, 0.6, 0.5))),
         fuzz: 0.,
     };
     world.add(Box::new(Sphere::new(
@@ -95,6 +91,8 @@ fn main() {
         material_3,
     )));
 
+    let world = BvhNode::new(world);
+
     let camera = Camera::builder()
         .aspect_ratio(16. / 9.)
         .image_width(1200)
@@ -108,5 +106,5 @@ fn main() {
         .focus_dist(10.)
         .build();
 
-    camera.render(&world);
+    camera.render(&world, &HittableList::default());
 }
@@ -0,0 +1,194 @@
+use std::ops::Mul;
+
+use crate::vec3::{Point3, Vec3};
+
+/// Row-major 4x4 matrix for affine transforms of points and vectors
+#[derive(Clone)]
+pub struct Mat4 {
+    m: [[f64; 4]; 4],
+}
+
+impl Mat4 {
+    #[must_use]
+    pub const fn new(m: [[f64; 4]; 4]) -> Self {
+        Self { m }
+    }
+
+    #[must_use]
+    pub const fn identity() -> Self {
+        Self::new([
+            [1., 0., 0., 0.],
+            [0., 1., 0., 0.],
+            [0., 0., 1., 0.],
+            [0., 0., 0., 1.],
+        ])
+    }
+
+    #[must_use]
+    pub fn translation(offset: &Vec3) -> Self {
+        Self::new([
+            [1., 0., 0., offset.x()],
+            [0., 1., 0., offset.y()],
+            [0., 0., 1., offset.z()],
+            [0., 0., 0., 1.],
+        ])
+    }
+
+    #[must_use]
+    pub fn scaling(factors: &Vec3) -> Self {
+        Self::new([
+            [factors.x(), 0., 0., 0.],
+            [0., factors.y(), 0., 0.],
+            [0., 0., factors.z(), 0.],
+            [0., 0., 0., 1.],
+        ])
+    }
+
+    /// Rotation by `degrees` counterclockwise about `axis`, looking down the
+    /// axis towards the origin
+    #[must_use]
+    pub fn rotation(axis: &Vec3, degrees: f64) -> Self {
+        let axis = axis.unit();
+        let (sin, cos) = degrees.to_radians().sin_cos();
+        let k = 1. - cos;
+        let (x, y, z) = (axis.x(), axis.y(), axis.z());
+
+        Self::new([
+            [
+                (x * x).mul_add(k, cos),
+                (x * y).mul_add(k, -z * sin),
+                (x * z).mul_add(k, y * sin),
+                0.,
+            ],
+            [
+                (y * x).mul_add(k, z * sin),
+                (y * y).mul_add(k, cos),
+                (y * z).mul_add(k, -x * sin),
+                0.,
+            ],
+            [
+                (z * x).mul_add(k, -y * sin),
+                (z * y).mul_add(k, x * sin),
+                (z * z).mul_add(k, cos),
+                0.,
+            ],
+            [0., 0., 0., 1.],
+        ])
+    }
+
+    #[must_use]
+    pub fn rotation_x(degrees: f64) -> Self {
+        Self::rotation(&Vec3::new(1., 0., 0.), degrees)
+    }
+
+    #[must_use]
+    pub fn rotation_y(degrees: f64) -> Self {
+        Self::rotation(&Vec3::new(0., 1., 0.), degrees)
+    }
+
+    #[must_use]
+    pub fn rotation_z(degrees: f64) -> Self {
+        Self::rotation(&Vec3::new(0., 0., 1.), degrees)
+    }
+
+    #[must_use]
+    pub const fn get(&self, row: usize, col: usize) -> f64 {
+        self.m[row][col]
+    }
+
+    #[must_use]
+    pub fn transpose(&self) -> Self {
+        let mut m = [[0.; 4]; 4];
+        for (row, values) in m.iter_mut().enumerate() {
+            for (col, value) in values.iter_mut().enumerate() {
+                *value = self.m[col][row];
+            }
+        }
+        Self::new(m)
+    }
+
+    /// Determinant of the linear part, the factor by which the transform scales
+    /// volumes (negated if it mirrors them)
+    #[must_use]
+    pub fn linear_determinant(&self) -> f64 {
+        let m = &self.m;
+        let minor = |a: usize, b: usize| m[1][a].mul_add(m[2][b], -m[1][b] * m[2][a]);
+        m[0][0].mul_add(
+            minor(1, 2),
+            m[0][1].mul_add(-minor(0, 2), m[0][2] * minor(0, 1)),
+        )
+    }
+
+    /// Returns the inverse matrix, or `None` if the matrix is singular.
+    /// Uses Gauss-Jordan elimination with partial pivoting.
+    #[must_use]
+    pub fn inverse(&self) -> Option<Self> {
+        let mut a = self.m;
+        let mut inv = Self::identity().m;
+
+        for col in 0..4 {
+            // Swap in the row with the largest pivot for numerical stability
+            let pivot = (col..4).max_by(|&i, &j| a[i][col].abs().total_cmp(&a[j][col].abs()))?;
+            if a[pivot][col].abs() < 1e-12 {
+                return None;
+            }
+            a.swap(col, pivot);
+            inv.swap(col, pivot);
+
+            let scale = 1. / a[col][col];
+            for k in 0..4 {
+                a[col][k] *= scale;
+                inv[col][k] *= scale;
+            }
+
+            for row in 0..4 {
+                if row == col {
+                    continue;
+                }
+                let factor = a[row][col];
+                for k in 0..4 {
+                    a[row][k] = factor.mul_add(-a[col][k], a[row][k]);
+                    inv[row][k] = factor.mul_add(-inv[col][k], inv[row][k]);
+                }
+            }
+        }
+
+        Some(Self::new(inv))
+    }
+
+    /// Applies the full affine transform, including translation, to a point
+    #[must_use]
+    pub fn transform_point(&self, p: &Point3) -> Point3 {
+        let row = |r: [f64; 4]| r[0].mul_add(p.x(), r[1].mul_add(p.y(), r[2].mul_add(p.z(), r[3])));
+        Point3::new(row(self.m[0]), row(self.m[1]), row(self.m[2]))
+    }
+
+    /// Applies the linear part of the transform to a direction, ignoring translation
+    #[must_use]
+    pub fn transform_vector(&self, v: &Vec3) -> Vec3 {
+        let row = |r: [f64; 4]| r[0].mul_add(v.x(), r[1].mul_add(v.y(), r[2] * v.z()));
+        Vec3::new(row(self.m[0]), row(self.m[1]), row(self.m[2]))
+    }
+}
+
+impl Mul<&Mat4> for &Mat4 {
+    type Output = Mat4;
+
+    fn mul(self, rhs: &Mat4) -> Self::Output {
+        let mut m = [[0.; 4]; 4];
+        for (row, values) in m.iter_mut().enumerate() {
+            for (col, value) in values.iter_mut().enumerate() {
+                *value = (0..4).map(|k| self.m[row][k] * rhs.m[k][col]).sum();
+            }
+        }
+        Mat4::new(m)
+    }
+}
+
+impl Mul for Mat4 {
+    type Output = Self;
+
+    fn mul(self, rhs: Self) -> Self::Output {
+        &self * &rhs
+    }
+}
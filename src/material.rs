@@ -1,18 +1,39 @@
+use std::f64::consts::PI;
+use std::sync::Arc;
+
 use rand::{Rng, thread_rng};
 
 use crate::hittable::HitRecord;
 use crate::ray::Ray;
+use crate::texture::Texture;
 use crate::vec3::{Color, Vec3};
 
 #[derive(Clone)]
 pub enum Material {
-    Lambertian { albedo: Color },
-    Metal { albedo: Color, fuzz: f64 },
-    Dielectric { refraction_index: f64, color: Color },
+    Lambertian {
+        albedo: Arc<dyn Texture>,
+    },
+    Metal {
+        albedo: Arc<dyn Texture>,
+        fuzz: f64,
+    },
+    Dielectric {
+        refraction_index: f64,
+        color: Arc<dyn Texture>,
+    },
+    Isotropic {
+        albedo: Arc<dyn Texture>,
+    },
+    /// Emits `intensity` times the texture color from both sides, and scatters nothing
+    DiffuseLight {
+        emit: Arc<dyn Texture>,
+        intensity: f64,
+    },
 }
 
 impl Material {
-    pub fn scatter(&self, ray: &Ray, hit_record: &HitRecord) -> Option<(&Color, Ray)> {
+    #[must_use]
+    pub fn scatter(&self, ray: &Ray, hit_record: &HitRecord) -> Option<(Color, Ray)> {
         match self {
             Self::Lambertian { albedo } => {
                 let mut scatter_direction = hit_record.normal() + Vec3::random_unit_vector();
@@ -22,8 +43,8 @@ impl Material {
                     scatter_direction = hit_record.normal().clone();
                 }
 
-                let scattered = Ray::new(hit_record.p().clone(), scatter_direction);
-                let attenuation = albedo;
+                let scattered = Ray::new(hit_record.p().clone(), scatter_direction, ray.time());
+                let attenuation = albedo.value(hit_record.u(), hit_record.v(), hit_record.p());
 
                 Some((attenuation, scattered))
             }
@@ -31,8 +52,8 @@ impl Material {
             Self::Metal { albedo, fuzz } => {
                 let reflected = ray.direction().reflect(hit_record.normal());
                 let reflected = reflected.unit() + (*fuzz * Vec3::random_unit_vector());
-                let scattered = Ray::new(hit_record.p().clone(), reflected);
-                let attenuation = albedo;
+                let scattered = Ray::new(hit_record.p().clone(), reflected, ray.time());
+                let attenuation = albedo.value(hit_record.u(), hit_record.v(), hit_record.p());
                 if scattered.direction().dot(hit_record.normal()) > 0. {
                     Some((attenuation, scattered))
                 } else {
@@ -64,9 +85,99 @@ impl Material {
                         unit_direction.refract(hit_record.normal(), refraction_ratio)
                     };
 
-                let scattered = Ray::new(hit_record.p().clone(), direction);
-                Some((color, scattered))
+                let scattered = Ray::new(hit_record.p().clone(), direction, ray.time());
+                let attenuation = color.value(hit_record.u(), hit_record.v(), hit_record.p());
+                Some((attenuation, scattered))
+            }
+
+            Self::Isotropic { albedo } => {
+                // Scatter uniformly in all directions, as inside a participating medium
+                let scattered = Ray::new(
+                    hit_record.p().clone(),
+                    Vec3::random_unit_vector(),
+                    ray.time(),
+                );
+                let attenuation = albedo.value(hit_record.u(), hit_record.v(), hit_record.p());
+                Some((attenuation, scattered))
+            }
+
+            Self::DiffuseLight { .. } => None,
+        }
+    }
+
+    /// Whether the material scatters into a single direction, like glass and
+    /// perfect mirrors. Such materials cannot be lit by sampled lights, and
+    /// `eval` and `pdf` are zero for them.
+    #[must_use]
+    pub fn is_specular(&self) -> bool {
+        match self {
+            Self::Metal { fuzz, .. } => *fuzz <= 0.,
+            Self::Dielectric { .. } | Self::DiffuseLight { .. } => true,
+            Self::Lambertian { .. } | Self::Isotropic { .. } => false,
+        }
+    }
+
+    /// Fraction of light arriving from `direction` that the material scatters
+    /// back along the incoming `ray`, including the cosine foreshortening at
+    /// surfaces.
+    #[must_use]
+    pub fn eval(&self, ray: &Ray, hit_record: &HitRecord, direction: &Vec3) -> Color {
+        let (u, v, p) = (hit_record.u(), hit_record.v(), hit_record.p());
+        match self {
+            Self::Lambertian { albedo } => {
+                let cosine = hit_record.normal().dot(&direction.unit()).max(0.);
+                (cosine / PI) * albedo.value(u, v, p)
+            }
+            // Every direction scatter can reach comes back with the albedo as its
+            // weight, so the response is the albedo times the sampling density
+            Self::Metal { albedo, .. } => {
+                self.pdf(ray, hit_record, direction) * albedo.value(u, v, p)
+            }
+            Self::Isotropic { albedo } => (1. / (4. * PI)) * albedo.value(u, v, p),
+            Self::Dielectric { .. } | Self::DiffuseLight { .. } => Color::new(0., 0., 0.),
+        }
+    }
+
+    /// Probability density over solid angle with which `scatter` sends the
+    /// incoming `ray` off along `direction`
+    #[must_use]
+    pub fn pdf(&self, ray: &Ray, hit_record: &HitRecord, direction: &Vec3) -> f64 {
+        match self {
+            Self::Lambertian { .. } => hit_record.normal().dot(&direction.unit()).max(0.) / PI,
+            Self::Metal { fuzz, .. } if *fuzz > 0. => {
+                let direction = direction.unit();
+                if direction.dot(hit_record.normal()) <= 0. {
+                    return 0.;
+                }
+                let reflected = ray.direction().reflect(hit_record.normal()).unit();
+                fuzzy_reflection_pdf(&reflected, *fuzz, &direction)
             }
+            Self::Isotropic { .. } => 1. / (4. * PI),
+            Self::Metal { .. } | Self::Dielectric { .. } | Self::DiffuseLight { .. } => 0.,
+        }
+    }
+
+    /// Base color of the material at the hit point
+    #[must_use]
+    pub fn albedo(&self, hit_record: &HitRecord) -> Color {
+        let texture = match self {
+            Self::Lambertian { albedo }
+            | Self::Metal { albedo, .. }
+            | Self::Isotropic { albedo } => albedo,
+            Self::Dielectric { color, .. } => color,
+            Self::DiffuseLight { emit, .. } => emit,
+        };
+        texture.value(hit_record.u(), hit_record.v(), hit_record.p())
+    }
+
+    /// Light given off by the material at the hit point, black for anything but lights
+    #[must_use]
+    pub fn emitted(&self, hit_record: &HitRecord) -> Color {
+        match self {
+            Self::DiffuseLight { emit, intensity } => {
+                *intensity * emit.value(hit_record.u(), hit_record.v(), hit_record.p())
+            }
+            _ => Color::new(0., 0., 0.),
         }
     }
 }
@@ -77,3 +188,28 @@ fn reflectance(cosine: f64, refraction_index: f64) -> f64 {
     let r0 = r0.powi(2);
     (1. - r0).mul_add((1. - cosine).powi(5), r0)
 }
+
+/// Density over solid angle of the unit `direction` for a fuzzy reflection,
+/// which aims at a uniformly random point on a sphere of radius `fuzz` around
+/// the tip of the unit `reflected` vector.
+///
+/// The density on the sphere is 1 / (4 pi fuzz^2). Each point where the
+/// direction pierces the sphere, at distance t and meeting its surface at an
+/// angle with cosine c, contributes t^2 / c times that.
+fn fuzzy_reflection_pdf(reflected: &Vec3, fuzz: f64, direction: &Vec3) -> f64 {
+    // Solve |t * direction - reflected| = fuzz for t
+    let b = direction.dot(reflected);
+    let discriminant = b.mul_add(b, fuzz.mul_add(fuzz, -1.));
+    if discriminant <= 0. {
+        return 0.;
+    }
+
+    // The cosine at either crossing is sqrt(discriminant) / fuzz. It vanishes
+    // where the direction grazes the sphere, so keep the density finite there.
+    let root = discriminant.sqrt().max(1e-6);
+    [b - root, b + root]
+        .into_iter()
+        .filter(|&t| t > 0.)
+        .map(|t| t * t / (4. * PI * fuzz * root))
+        .sum()
+}
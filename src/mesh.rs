@@ -0,0 +1,203 @@
+use std::sync::Arc;
+
+use rand::Rng;
+
+use crate::aabb::Aabb;
+use crate::bvh::BvhNode;
+use crate::hittable::{HitRecord, HitTrace, Hittable, area_to_solid_angle_pdf};
+use crate::hittable_list::HittableList;
+use crate::interval::Interval;
+use crate::material::Material;
+use crate::ray::Ray;
+use crate::triangle::{intersect_watertight, sample_triangle, triangle_hit_record};
+use crate::vec3::{Point3, Vec3};
+
+/// Vertex and index buffers shared by every triangle of a mesh
+struct MeshData {
+    positions: Vec<Point3>,
+    normals: Option<Vec<Vec3>>, // Per-vertex shading normals, indexed like `positions`
+    uvs: Option<Vec<(f64, f64)>>, // Per-vertex texture coordinates, indexed like `positions`
+    indices: Vec<[usize; 3]>,
+    mat: Material,
+}
+
+/// Indexed triangle mesh. Triangles reference a single shared vertex buffer
+/// and are organized in a BVH of their own.
+///
+/// As a light, points are sampled uniformly over the whole surface, picking
+/// triangles in proportion to their area.
+pub struct TriangleMesh {
+    mesh: Arc<MeshData>,
+    triangles: BvhNode,
+    area_cdf: Vec<f64>, // Running total of the triangle areas, in face order
+}
+
+impl TriangleMesh {
+    /// Create a mesh from vertex positions, optional per-vertex normals and
+    /// texture coordinates, and one `[a, b, c]` index triple per triangle
+    ///
+    /// # Panics
+    ///
+    /// Panics if an index is out of bounds for `positions`, or if `normals` or
+    /// `uvs` is given with a different length than `positions`.
+    #[must_use]
+    pub fn new(
+        positions: Vec<Point3>,
+        normals: Option<Vec<Vec3>>,
+        uvs: Option<Vec<(f64, f64)>>,
+        indices: Vec<[usize; 3]>,
+        mat: Material,
+    ) -> Self {
+        assert!(
+            indices.iter().flatten().all(|&i| i < positions.len()),
+            "Triangle index out of bounds"
+        );
+        if let Some(normals) = &normals {
+            assert_eq!(
+                normals.len(),
+                positions.len(),
+                "Mesh must have one normal per vertex"
+            );
+        }
+        if let Some(uvs) = &uvs {
+            assert_eq!(
+                uvs.len(),
+                positions.len(),
+                "Mesh must have one texture coordinate per vertex"
+            );
+        }
+
+        let mesh = Arc::new(MeshData {
+            positions,
+            normals,
+            uvs,
+            indices,
+            mat,
+        });
+
+        let mut triangles = HittableList::default();
+        let mut area_cdf = Vec::with_capacity(mesh.indices.len());
+        let mut total_area = 0.;
+        for face in 0..mesh.indices.len() {
+            triangles.add(Box::new(MeshTriangle::new(Arc::clone(&mesh), face)));
+
+            let [a, b, c] = mesh.indices[face].map(|i| &mesh.positions[i]);
+            total_area += 0.5 * (b - a).cross(&(c - a)).length();
+            area_cdf.push(total_area);
+        }
+
+        Self {
+            mesh,
+            triangles: BvhNode::new(triangles),
+            area_cdf,
+        }
+    }
+
+    /// Surface area of the whole mesh
+    fn area(&self) -> f64 {
+        self.area_cdf.last().copied().unwrap_or(0.)
+    }
+}
+
+impl Hittable for TriangleMesh {
+    fn hit(&self, ray: &Ray, ray_t: &Interval) -> Option<HitRecord> {
+        self.triangles.hit(ray, ray_t)
+    }
+
+    fn bounding_box(&self) -> &Aabb {
+        self.triangles.bounding_box()
+    }
+
+    fn hit_traced(&self, ray: &Ray, ray_t: &Interval, trace: &mut HitTrace) -> Option<HitRecord> {
+        self.triangles.hit_traced(ray, ray_t, trace)
+    }
+
+    fn occluded(&self, ray: &Ray, ray_t: &Interval) -> bool {
+        self.triangles.occluded(ray, ray_t)
+    }
+
+    fn sample(&self, origin: &Point3, time: f64) -> Option<(Vec3, f64)> {
+        let area = self.area();
+        if area <= 0. {
+            return None;
+        }
+        let target = rand::thread_rng().gen::<f64>() * area;
+        let face = self
+            .area_cdf
+            .partition_point(|&running| running <= target)
+            .min(self.area_cdf.len() - 1);
+
+        let [a, b, c] = self.mesh.indices[face].map(|i| &self.mesh.positions[i]);
+        let direction = sample_triangle(a, b, c) - origin;
+        let pdf = self.pdf(origin, &direction, time);
+        (pdf > 0.).then_some((direction, pdf))
+    }
+
+    /// The direction may pass through several parts of the mesh, any of which
+    /// could have been sampled, so the densities of all the crossings add up
+    fn pdf(&self, origin: &Point3, direction: &Vec3, time: f64) -> f64 {
+        let area = self.area();
+        if area <= 0. {
+            return 0.;
+        }
+        let ray = Ray::new(origin.clone(), direction.clone(), time);
+        self.hit_all(&ray, &Interval::new(0.001, f64::INFINITY))
+            .iter()
+            .map(|hit_record| area_to_solid_angle_pdf(&ray, hit_record, area))
+            .sum()
+    }
+}
+
+/// A single face of a `TriangleMesh`, referring back into the shared buffers
+struct MeshTriangle {
+    mesh: Arc<MeshData>,
+    face: usize,
+    bbox: Aabb,
+}
+
+impl MeshTriangle {
+    fn new(mesh: Arc<MeshData>, face: usize) -> Self {
+        let [a, b, c] = mesh.indices[face].map(|i| &mesh.positions[i]);
+        let bbox = Aabb::union(&Aabb::from_points(a, b), &Aabb::from_points(b, c));
+        Self { mesh, face, bbox }
+    }
+}
+
+impl Hittable for MeshTriangle {
+    fn hit(&self, ray: &Ray, ray_t: &Interval) -> Option<HitRecord> {
+        let indices = self.mesh.indices[self.face];
+        let [a, b, c] = indices.map(|i| &self.mesh.positions[i]);
+        let (t, b0, b1, b2) = intersect_watertight(ray, ray_t, a, b, c)?;
+
+        let shading_normal = self.mesh.normals.as_ref().map(|normals| {
+            let [na, nb, nc] = indices.map(|i| &normals[i]);
+            b0 * na + b1 * nb + b2 * nc
+        });
+        let uv = self.mesh.uvs.as_ref().map(|uvs| {
+            let [ta, tb, tc] = indices.map(|i| uvs[i]);
+            (
+                b0.mul_add(ta.0, b1.mul_add(tb.0, b2 * tc.0)),
+                b0.mul_add(ta.1, b1.mul_add(tb.1, b2 * tc.1)),
+            )
+        });
+
+        Some(triangle_hit_record(
+            ray,
+            t,
+            [a, b, c],
+            (b1, b2),
+            shading_normal,
+            uv,
+            self.mesh.mat.clone(),
+        ))
+    }
+
+    fn bounding_box(&self) -> &Aabb {
+        &self.bbox
+    }
+
+    fn occluded(&self, ray: &Ray, ray_t: &Interval) -> bool {
+        let [a, b, c] = self.mesh.indices[self.face].map(|i| &self.mesh.positions[i]);
+        intersect_watertight(ray, ray_t, a, b, c).is_some()
+    }
+}
@@ -0,0 +1,210 @@
+//! Procedural noise functions for textures
+//!
+//! Both noise generators are seeded, so the same seed always produces the same
+//! pattern regardless of how many threads are rendering.
+
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::{Rng, SeedableRng};
+
+use crate::vec3::{Point3, Vec3};
+
+const POINT_COUNT: usize = 256;
+
+/// Ken Perlin's gradient noise, with random unit gradients at the integer
+/// lattice points
+pub struct Perlin {
+    gradients: Vec<Vec3>,
+    perm_x: Vec<usize>,
+    perm_y: Vec<usize>,
+    perm_z: Vec<usize>,
+}
+
+impl Perlin {
+    #[must_use]
+    pub fn new(seed: u64) -> Self {
+        let mut rng = StdRng::seed_from_u64(seed);
+
+        let gradients = (0..POINT_COUNT)
+            .map(|_| {
+                Vec3::new(
+                    rng.gen_range(-1.0..1.0),
+                    rng.gen_range(-1.0..1.0),
+                    rng.gen_range(-1.0..1.0),
+                )
+                .unit()
+            })
+            .collect();
+
+        let mut permutation = || {
+            let mut perm: Vec<usize> = (0..POINT_COUNT).collect();
+            perm.shuffle(&mut rng);
+            perm
+        };
+        let perm_x = permutation();
+        let perm_y = permutation();
+        let perm_z = permutation();
+
+        Self {
+            gradients,
+            perm_x,
+            perm_y,
+            perm_z,
+        }
+    }
+
+    /// Smoothly varying noise in about [-1, 1], zero at every lattice point
+    #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+    #[must_use]
+    pub fn noise(&self, p: &Point3) -> f64 {
+        let (fx, fy, fz) = (p.x().floor(), p.y().floor(), p.z().floor());
+        let (i, j, k) = (fx as i64, fy as i64, fz as i64);
+        let offset = Vec3::new(p.x() - fx, p.y() - fy, p.z() - fz);
+
+        // Hermite smoothing of the interpolation weights hides the lattice
+        let smooth = |t: f64| t * t * 2_f64.mul_add(-t, 3.);
+        let weights = [smooth(offset.x()), smooth(offset.y()), smooth(offset.z())];
+
+        let mut accum = 0.;
+        for di in 0..2_u8 {
+            for dj in 0..2_u8 {
+                for dk in 0..2_u8 {
+                    let lattice =
+                        |index: i64, delta: u8| ((index + i64::from(delta)) & 255) as usize;
+                    let gradient = &self.gradients[self.perm_x[lattice(i, di)]
+                        ^ self.perm_y[lattice(j, dj)]
+                        ^ self.perm_z[lattice(k, dk)]];
+
+                    let corner = Vec3::new(f64::from(di), f64::from(dj), f64::from(dk));
+                    let weight = |axis: usize| {
+                        if corner[axis] > 0. {
+                            weights[axis]
+                        } else {
+                            1. - weights[axis]
+                        }
+                    };
+                    accum += weight(0) * weight(1) * weight(2) * gradient.dot(&(&offset - corner));
+                }
+            }
+        }
+        accum
+    }
+
+    /// Fractional Brownian motion: `octaves` layers of noise, each at `lacunarity`
+    /// times the frequency and `gain` times the amplitude of the one before
+    #[must_use]
+    pub fn fbm(&self, p: &Point3, octaves: u32, lacunarity: f64, gain: f64) -> f64 {
+        let mut accum = 0.;
+        let mut p = p.clone();
+        let mut amplitude = 1.;
+
+        for _ in 0..octaves {
+            accum += amplitude * self.noise(&p);
+            amplitude *= gain;
+            p *= lacunarity;
+        }
+        accum
+    }
+
+    /// Sum of the absolute value of `octaves` layers of noise, doubling the
+    /// frequency and halving the amplitude each time. Always positive, with
+    /// sharp creases where the noise crosses zero.
+    #[must_use]
+    pub fn turbulence(&self, p: &Point3, octaves: u32) -> f64 {
+        let mut accum = 0.;
+        let mut p = p.clone();
+        let mut amplitude = 1.;
+
+        for _ in 0..octaves {
+            accum += amplitude * self.noise(&p).abs();
+            amplitude *= 0.5;
+            p *= 2.;
+        }
+        accum
+    }
+}
+
+/// Distances from a point to the nearest features of a Worley pattern
+pub struct WorleySample {
+    /// Distance to the nearest feature point
+    pub f1: f64,
+    /// Distance to the second nearest feature point
+    pub f2: f64,
+    /// Random value in [0, 1) shared by every point in the nearest feature's cell
+    pub cell_value: f64,
+}
+
+/// Steven Worley's cellular noise, with one randomly placed feature point in
+/// every unit cube of space
+pub struct Worley {
+    seed: u64,
+}
+
+impl Worley {
+    #[must_use]
+    pub const fn new(seed: u64) -> Self {
+        Self { seed }
+    }
+
+    #[allow(clippy::cast_possible_truncation)]
+    #[must_use]
+    pub fn sample(&self, p: &Point3) -> WorleySample {
+        let (i, j, k) = (
+            p.x().floor() as i64,
+            p.y().floor() as i64,
+            p.z().floor() as i64,
+        );
+
+        let mut f1 = f64::INFINITY;
+        let mut f2 = f64::INFINITY;
+        let mut cell_value = 0.;
+
+        // The nearest feature point always lies in one of the neighbouring cells,
+        // and the second nearest almost always does
+        for ci in i - 1..=i + 1 {
+            for cj in j - 1..=j + 1 {
+                for ck in k - 1..=k + 1 {
+                    let hash = self.hash(ci, cj, ck);
+                    #[allow(clippy::cast_precision_loss)]
+                    let feature = Point3::new(
+                        ci as f64 + to_unit(mix(hash ^ 1)),
+                        cj as f64 + to_unit(mix(hash ^ 2)),
+                        ck as f64 + to_unit(mix(hash ^ 3)),
+                    );
+
+                    let distance = (&feature - p).length();
+                    if distance < f1 {
+                        f2 = f1;
+                        f1 = distance;
+                        cell_value = to_unit(hash);
+                    } else if distance < f2 {
+                        f2 = distance;
+                    }
+                }
+            }
+        }
+
+        WorleySample { f1, f2, cell_value }
+    }
+
+    #[allow(clippy::cast_sign_loss)]
+    fn hash(&self, i: i64, j: i64, k: i64) -> u64 {
+        [i, j, k]
+            .into_iter()
+            .fold(self.seed, |hash, coord| mix(hash ^ coord as u64))
+    }
+}
+
+/// `SplitMix64` finalizer, scrambling every input bit into every output bit
+const fn mix(mut x: u64) -> u64 {
+    x = x.wrapping_add(0x9e37_79b9_7f4a_7c15);
+    x = (x ^ (x >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    x = (x ^ (x >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    x ^ (x >> 31)
+}
+
+/// Maps a hash onto [0, 1) using its top 53 bits
+#[allow(clippy::cast_precision_loss)]
+fn to_unit(hash: u64) -> f64 {
+    (hash >> 11) as f64 / (1_u64 << 53) as f64
+}
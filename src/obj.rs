@@ -0,0 +1,625 @@
+//! Wavefront OBJ/MTL import
+//!
+//! Supports vertex positions, normals and texture coordinates, polygonal faces
+//! (triangulated as fans), negative indices and `usemtl` material groups. MTL
+//! materials are mapped onto the closest `Material` variant.
+
+use std::collections::HashMap;
+use std::collections::hash_map::Entry;
+use std::fmt::{Display, Formatter};
+use std::fs::File;
+use std::io::{self, BufRead, BufReader};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use image::ImageError;
+
+use crate::hittable_list::HittableList;
+use crate::image_texture::ImageTexture;
+use crate::material::Material;
+use crate::mesh::TriangleMesh;
+use crate::texture::{SolidColor, Texture};
+use crate::vec3::{Color, Point3, Vec3};
+
+#[derive(Debug)]
+pub enum ObjError {
+    Io {
+        path: PathBuf,
+        source: io::Error,
+    },
+    Parse {
+        path: PathBuf,
+        line: usize,
+        message: String,
+    },
+    Image {
+        path: PathBuf,
+        source: ImageError,
+    },
+}
+
+impl Display for ObjError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Io { path, source } => write!(f, "{}: {source}", path.display()),
+            Self::Parse {
+                path,
+                line,
+                message,
+            } => write!(f, "{}:{line}: {message}", path.display()),
+            Self::Image { path, source } => write!(f, "{}: {source}", path.display()),
+        }
+    }
+}
+
+impl std::error::Error for ObjError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Io { source, .. } => Some(source),
+            Self::Parse { .. } => None,
+            Self::Image { source, .. } => Some(source),
+        }
+    }
+}
+
+/// Load an OBJ file as one triangle mesh per material. Faces before the first
+/// `usemtl` statement use `default_material`.
+///
+/// # Errors
+///
+/// Returns an error if the OBJ file, a referenced MTL library or a texture image
+/// cannot be read, or if the OBJ or MTL file contains a malformed statement or
+/// refers to an unknown material.
+pub fn load(path: impl AsRef<Path>, default_material: Material) -> Result<HittableList, ObjError> {
+    let path = path.as_ref();
+    let lines = read_lines(path)?;
+    let base_dir = path.parent().unwrap_or_else(|| Path::new(""));
+
+    let mut positions: Vec<Point3> = Vec::new();
+    let mut normals: Vec<Vec3> = Vec::new();
+    let mut uvs: Vec<(f64, f64)> = Vec::new();
+
+    let mut materials: HashMap<String, MtlMaterial> = HashMap::new();
+    let mut groups: Vec<(Material, MeshBuilder)> = vec![(default_material, MeshBuilder::default())];
+    let mut group_of_material: HashMap<String, usize> = HashMap::new();
+    let mut current_group = 0;
+
+    for (line_no, line) in lines.iter().enumerate() {
+        let mut parser = LineParser::new(path, line_no + 1, line);
+        let Some(keyword) = parser.next_token() else {
+            continue;
+        };
+
+        match keyword {
+            "v" => {
+                let [x, y, z] = parser.floats()?;
+                positions.push(Point3::new(x, y, z));
+            }
+            "vn" => {
+                let [x, y, z] = parser.floats()?;
+                normals.push(Vec3::new(x, y, z));
+            }
+            "vt" => {
+                let u = parser.float()?;
+                let v = parser.optional_float()?.unwrap_or(0.);
+                uvs.push((u, v));
+            }
+            "f" => {
+                let mut corners = Vec::new();
+                while let Some(token) = parser.next_token() {
+                    corners.push(parser.face_corner(
+                        token,
+                        positions.len(),
+                        uvs.len(),
+                        normals.len(),
+                    )?);
+                }
+                if corners.len() < 3 {
+                    return Err(parser.error("face needs at least three vertices"));
+                }
+
+                let builder = &mut groups[current_group].1;
+                let first = builder.vertex(&corners[0], &positions, &uvs, &normals);
+                for pair in corners[1..].windows(2) {
+                    let b = builder.vertex(&pair[0], &positions, &uvs, &normals);
+                    let c = builder.vertex(&pair[1], &positions, &uvs, &normals);
+                    builder.indices.push([first, b, c]);
+                }
+            }
+            "mtllib" => {
+                let file = parser.rest()?;
+                materials.extend(load_mtl(&base_dir.join(file))?);
+            }
+            "usemtl" => {
+                let name = parser.rest()?;
+                let Some(mtl) = materials.get(name) else {
+                    return Err(parser.error(&format!("unknown material '{name}'")));
+                };
+                current_group = match group_of_material.entry(name.to_owned()) {
+                    Entry::Occupied(entry) => *entry.get(),
+                    Entry::Vacant(entry) => {
+                        groups.push((mtl.to_material()?, MeshBuilder::default()));
+                        *entry.insert(groups.len() - 1)
+                    }
+                };
+            }
+            // Object/group names, smoothing groups and anything else don't affect geometry
+            _ => {}
+        }
+    }
+
+    let mut list = HittableList::default();
+    for (material, builder) in groups {
+        if !builder.indices.is_empty() {
+            list.add(Box::new(builder.build(material)));
+        }
+    }
+
+    Ok(list)
+}
+
+fn read_lines(path: &Path) -> Result<Vec<String>, ObjError> {
+    let io_error = |source| ObjError::Io {
+        path: path.to_path_buf(),
+        source,
+    };
+    let file = File::open(path).map_err(io_error)?;
+    BufReader::new(file)
+        .lines()
+        .collect::<Result<_, _>>()
+        .map_err(io_error)
+}
+
+/// Resolved indices of one face corner into the OBJ position, texture
+/// coordinate and normal lists
+#[derive(Clone, PartialEq, Eq, Hash)]
+struct Corner {
+    position: usize,
+    uv: Option<usize>,
+    normal: Option<usize>,
+}
+
+/// Accumulates a single mesh, merging face corners that share all attributes
+/// into one vertex
+#[derive(Default)]
+struct MeshBuilder {
+    positions: Vec<Point3>,
+    normals: Vec<Vec3>,
+    uvs: Vec<(f64, f64)>,
+    indices: Vec<[usize; 3]>,
+    has_normals: bool,
+    has_uvs: bool,
+    vertices: HashMap<Corner, usize>,
+}
+
+impl MeshBuilder {
+    fn vertex(
+        &mut self,
+        corner: &Corner,
+        positions: &[Point3],
+        uvs: &[(f64, f64)],
+        normals: &[Vec3],
+    ) -> usize {
+        if let Some(&index) = self.vertices.get(corner) {
+            return index;
+        }
+
+        let index = self.positions.len();
+        self.positions.push(positions[corner.position].clone());
+        // Missing normals are left zero so the triangle falls back to its geometric normal
+        self.normals.push(
+            corner
+                .normal
+                .map_or_else(Vec3::default, |n| normals[n].clone()),
+        );
+        self.uvs.push(corner.uv.map_or((0., 0.), |t| uvs[t]));
+        self.has_normals |= corner.normal.is_some();
+        self.has_uvs |= corner.uv.is_some();
+
+        self.vertices.insert(corner.clone(), index);
+        index
+    }
+
+    fn build(self, material: Material) -> TriangleMesh {
+        TriangleMesh::new(
+            self.positions,
+            self.has_normals.then_some(self.normals),
+            self.has_uvs.then_some(self.uvs),
+            self.indices,
+            material,
+        )
+    }
+}
+
+/// The subset of an MTL material that we know how to render
+struct MtlMaterial {
+    diffuse: Color,               // Kd
+    specular: Color,              // Ks
+    specular_exponent: f64,       // Ns
+    refraction_index: f64,        // Ni
+    dissolve: f64,                // d, or 1 - Tr
+    transmission_filter: Color,   // Tf
+    diffuse_map: Option<PathBuf>, // map_Kd
+}
+
+impl MtlMaterial {
+    const fn default() -> Self {
+        Self {
+            diffuse: Color::new(0.8, 0.8, 0.8),
+            specular: Color::new(0., 0., 0.),
+            specular_exponent: 0.,
+            refraction_index: 1.,
+            dissolve: 1.,
+            transmission_filter: Color::new(1., 1., 1.),
+            diffuse_map: None,
+        }
+    }
+
+    /// Picks the `Material` variant closest to the MTL description: transparent
+    /// materials become glass, materials whose specular color outweighs their
+    /// diffuse color become metal, and everything else is diffuse. A diffuse
+    /// texture map replaces the diffuse color.
+    fn to_material(&self) -> Result<Material, ObjError> {
+        if self.dissolve < 1. {
+            return Ok(Material::Dielectric {
+                refraction_index: if self.refraction_index > 1. {
+                    self.refraction_index
+                } else {
+                    1.5
+                },
+                color: Arc::new(SolidColor::new(self.transmission_filter.clone())),
+            });
+        }
+
+        if max_component(&self.specular) > max_component(&self.diffuse) {
+            // Map the Phong exponent onto a roughness; high exponents are sharp reflections
+            let fuzz = (2. / (self.specular_exponent + 2.)).sqrt().min(1.);
+            return Ok(Material::Metal {
+                albedo: Arc::new(SolidColor::new(self.specular.clone())),
+                fuzz,
+            });
+        }
+
+        let albedo: Arc<dyn Texture> = match &self.diffuse_map {
+            Some(path) => Arc::new(ImageTexture::open(path).map_err(|source| ObjError::Image {
+                path: path.clone(),
+                source,
+            })?),
+            None => Arc::new(SolidColor::new(self.diffuse.clone())),
+        };
+        Ok(Material::Lambertian { albedo })
+    }
+}
+
+fn max_component(color: &Color) -> f64 {
+    color.x().max(color.y()).max(color.z())
+}
+
+fn load_mtl(path: &Path) -> Result<HashMap<String, MtlMaterial>, ObjError> {
+    let lines = read_lines(path)?;
+    let base_dir = path.parent().unwrap_or_else(|| Path::new(""));
+
+    let mut materials = HashMap::new();
+    let mut current: Option<(String, MtlMaterial)> = None;
+
+    for (line_no, line) in lines.iter().enumerate() {
+        let mut parser = LineParser::new(path, line_no + 1, line);
+        let Some(keyword) = parser.next_token() else {
+            continue;
+        };
+
+        if keyword == "newmtl" {
+            let name = parser.rest()?.to_owned();
+            if let Some((name, mtl)) = current.replace((name, MtlMaterial::default())) {
+                materials.insert(name, mtl);
+            }
+            continue;
+        }
+
+        let Some((_, mtl)) = current.as_mut() else {
+            // Statements before the first `newmtl` have nothing to apply to
+            continue;
+        };
+
+        match keyword {
+            "Kd" => mtl.diffuse = parser.color()?,
+            "Ks" => mtl.specular = parser.color()?,
+            "Tf" => mtl.transmission_filter = parser.color()?,
+            "Ns" => mtl.specular_exponent = parser.float()?,
+            "Ni" => mtl.refraction_index = parser.float()?,
+            "d" => mtl.dissolve = parser.float()?,
+            "Tr" => mtl.dissolve = 1. - parser.float()?,
+            "map_Kd" => mtl.diffuse_map = Some(base_dir.join(parser.rest()?)),
+            _ => {}
+        }
+    }
+
+    if let Some((name, mtl)) = current {
+        materials.insert(name, mtl);
+    }
+
+    Ok(materials)
+}
+
+/// Whitespace tokenizer over one line of an OBJ or MTL file, which knows its
+/// position so that errors can point back to it
+struct LineParser<'a> {
+    path: &'a Path,
+    line_no: usize,
+    content: &'a str,
+}
+
+impl<'a> LineParser<'a> {
+    fn new(path: &'a Path, line_no: usize, line: &'a str) -> Self {
+        // Everything after a '#' is a comment
+        let content = line.split_once('#').map_or(line, |(content, _)| content);
+        Self {
+            path,
+            line_no,
+            content: content.trim_start(),
+        }
+    }
+
+    fn error(&self, message: &str) -> ObjError {
+        ObjError::Parse {
+            path: self.path.to_path_buf(),
+            line: self.line_no,
+            message: message.to_owned(),
+        }
+    }
+
+    fn next_token(&mut self) -> Option<&'a str> {
+        let content = self.content.trim_start();
+        if content.is_empty() {
+            return None;
+        }
+
+        let end = content.find(char::is_whitespace).unwrap_or(content.len());
+        let (token, rest) = content.split_at(end);
+        self.content = rest;
+        Some(token)
+    }
+
+    /// Returns the remainder of the line, for names that may contain spaces
+    fn rest(&mut self) -> Result<&'a str, ObjError> {
+        let rest = self.content.trim();
+        self.content = "";
+        if rest.is_empty() {
+            Err(self.error("expected a name"))
+        } else {
+            Ok(rest)
+        }
+    }
+
+    fn optional_float(&mut self) -> Result<Option<f64>, ObjError> {
+        self.next_token()
+            .map(|token| {
+                token
+                    .parse()
+                    .map_err(|_| self.error(&format!("invalid number '{token}'")))
+            })
+            .transpose()
+    }
+
+    fn float(&mut self) -> Result<f64, ObjError> {
+        self.optional_float()?
+            .ok_or_else(|| self.error("expected a number"))
+    }
+
+    fn floats<const N: usize>(&mut self) -> Result<[f64; N], ObjError> {
+        let mut values = [0.; N];
+        for value in &mut values {
+            *value = self.float()?;
+        }
+        Ok(values)
+    }
+
+    /// Reads an MTL color, where a single value stands for a gray
+    fn color(&mut self) -> Result<Color, ObjError> {
+        let r = self.float()?;
+        let g = self.optional_float()?;
+        let b = self.optional_float()?;
+        match (g, b) {
+            (None, None) => Ok(Color::new(r, r, r)),
+            (Some(g), Some(b)) => Ok(Color::new(r, g, b)),
+            _ => Err(self.error("expected one or three color components")),
+        }
+    }
+
+    /// Parses a `v`, `v/vt`, `v//vn` or `v/vt/vn` face corner
+    fn face_corner(
+        &self,
+        token: &str,
+        position_count: usize,
+        uv_count: usize,
+        normal_count: usize,
+    ) -> Result<Corner, ObjError> {
+        let mut parts = token.split('/');
+        let position = parts.next().unwrap_or_default();
+        let uv = parts.next().filter(|part| !part.is_empty());
+        let normal = parts.next().filter(|part| !part.is_empty());
+        if parts.next().is_some() {
+            return Err(self.error(&format!("invalid face vertex '{token}'")));
+        }
+
+        Ok(Corner {
+            position: self.index(position, position_count, "vertex")?,
+            uv: uv
+                .map(|uv| self.index(uv, uv_count, "texture coordinate"))
+                .transpose()?,
+            normal: normal
+                .map(|normal| self.index(normal, normal_count, "normal"))
+                .transpose()?,
+        })
+    }
+
+    /// Resolves a one-based, or negative and relative to the end, OBJ index
+    fn index(&self, token: &str, count: usize, kind: &str) -> Result<usize, ObjError> {
+        let index: isize = token
+            .parse()
+            .map_err(|_| self.error(&format!("invalid {kind} index '{token}'")))?;
+
+        let resolved = if index > 0 {
+            index.unsigned_abs() - 1
+        } else {
+            count.wrapping_sub(index.unsigned_abs())
+        };
+
+        if index == 0 || resolved >= count {
+            return Err(self.error(&format!(
+                "{kind} index {index} out of range ({count} defined)"
+            )));
+        }
+
+        Ok(resolved)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parser(line: &str) -> LineParser<'_> {
+        LineParser::new(Path::new("test.obj"), 1, line)
+    }
+
+    /// Writes `contents` to a temporary OBJ file and loads it
+    fn load_str(name: &str, contents: &str) -> Result<HittableList, ObjError> {
+        let path = std::env::temp_dir().join(format!("obj_test_{}_{name}.obj", std::process::id()));
+        std::fs::write(&path, contents).expect("failed to write test OBJ");
+        let material = Material::Lambertian {
+            albedo: Arc::new(SolidColor::new(Color::new(0.5, 0.5, 0.5))),
+        };
+        let result = load(&path, material);
+        let _ = std::fs::remove_file(&path);
+        result
+    }
+
+    /// Line number and message of a parse error
+    fn parse_error<T>(result: Result<T, ObjError>) -> (usize, String) {
+        match result {
+            Err(ObjError::Parse { line, message, .. }) => (line, message),
+            Err(other) => panic!("expected a parse error, got '{other}'"),
+            Ok(_) => panic!("expected a parse error"),
+        }
+    }
+
+    #[test]
+    fn resolves_positive_and_negative_indices() {
+        let parser = parser("");
+        assert_eq!(parser.index("1", 3, "vertex").ok(), Some(0));
+        assert_eq!(parser.index("3", 3, "vertex").ok(), Some(2));
+        assert_eq!(parser.index("-1", 3, "vertex").ok(), Some(2));
+        assert_eq!(parser.index("-3", 3, "vertex").ok(), Some(0));
+    }
+
+    #[test]
+    fn rejects_out_of_range_indices() {
+        let parser = parser("");
+        for token in ["0", "4", "-4"] {
+            let (_, message) = parse_error(parser.index(token, 3, "vertex"));
+            assert_eq!(
+                message,
+                format!("vertex index {token} out of range (3 defined)")
+            );
+        }
+        let (_, message) = parse_error(parser.index("1", 0, "normal"));
+        assert_eq!(message, "normal index 1 out of range (0 defined)");
+    }
+
+    #[test]
+    fn parses_face_corners() {
+        let parser = parser("");
+        let corner = |token| parser.face_corner(token, 3, 2, 1).ok();
+
+        let full = corner("3/2/1").expect("v/vt/vn corner");
+        assert_eq!((full.position, full.uv, full.normal), (2, Some(1), Some(0)));
+        let no_uv = corner("1//1").expect("v//vn corner");
+        assert_eq!((no_uv.uv, no_uv.normal), (None, Some(0)));
+        let position_only = corner("-1").expect("v corner");
+        assert_eq!((position_only.position, position_only.uv), (2, None));
+
+        assert!(corner("1/1/1/1").is_none());
+        assert!(corner("a/1").is_none());
+        assert!(corner("1/3").is_none());
+    }
+
+    #[test]
+    fn rejects_malformed_numbers() {
+        let (_, message) = parse_error(parser("1 x 3").floats::<3>());
+        assert_eq!(message, "invalid number 'x'");
+        let (_, message) = parse_error(parser("1 2").floats::<3>());
+        assert_eq!(message, "expected a number");
+        let (_, message) = parse_error(parser("0.5 0.5").color());
+        assert_eq!(message, "expected one or three color components");
+    }
+
+    #[test]
+    fn ignores_comments() {
+        let mut parser = parser("  v 1 2 # 3");
+        assert_eq!(parser.next_token(), Some("v"));
+        assert_eq!(parser.floats::<2>().ok(), Some([1., 2.]));
+        assert_eq!(parser.next_token(), None);
+    }
+
+    #[test]
+    fn loads_polygons() {
+        let list = load_str(
+            "polygons",
+            "v 0 0 0\nv 1 0 0\nv 1 1 0\nv 0 1 0\n\n# A quad, split into two triangles\nf 1 2 3 4\nf -4 -3 -2\n",
+        );
+        assert_eq!(list.map(|list| list.len()).ok(), Some(1));
+    }
+
+    #[test]
+    fn reports_malformed_lines() {
+        let cases = [
+            (
+                "short_face",
+                "v 0 0 0\nv 1 0 0\nf 1 2\n",
+                3,
+                "face needs at least three vertices",
+            ),
+            (
+                "bad_vertex",
+                "v 0 0 0\nv 1 zero 0\n",
+                2,
+                "invalid number 'zero'",
+            ),
+            ("missing_coordinate", "v 0 0\n", 1, "expected a number"),
+            (
+                "out_of_range",
+                "v 0 0 0\nv 1 0 0\nv 1 1 0\nf 1 2 4\n",
+                4,
+                "vertex index 4 out of range (3 defined)",
+            ),
+            (
+                "normal_out_of_range",
+                "v 0 0 0\nv 1 0 0\nv 1 1 0\nvn 0 0 1\nf 1//1 2//1 3//2\n",
+                5,
+                "normal index 2 out of range (1 defined)",
+            ),
+            // Indices may only refer back to vertices defined earlier in the file
+            (
+                "forward_reference",
+                "v 0 0 0\nv 1 0 0\nf 1 2 3\nv 1 1 0\n",
+                3,
+                "vertex index 3 out of range (2 defined)",
+            ),
+            (
+                "unknown_material",
+                "usemtl missing\n",
+                1,
+                "unknown material 'missing'",
+            ),
+        ];
+
+        for (name, contents, line, message) in cases {
+            assert_eq!(
+                parse_error(load_str(name, contents)),
+                (line, message.to_owned()),
+                "{name}"
+            );
+        }
+    }
+}
@@ -0,0 +1,50 @@
+use crate::vec3::Vec3;
+
+/// Orthonormal basis, with `w` along a given direction
+pub struct Onb {
+    axis: [Vec3; 3],
+}
+
+impl Onb {
+    #[allow(clippy::many_single_char_names)]
+    #[must_use]
+    pub fn new(n: &Vec3) -> Self {
+        let w = n.unit();
+        let a = if w.x().abs() > 0.9 {
+            Vec3::new(0., 1., 0.)
+        } else {
+            Vec3::new(1., 0., 0.)
+        };
+        let v = w.cross(&a).unit();
+        let u = w.cross(&v);
+
+        Self { axis: [u, v, w] }
+    }
+
+    #[must_use]
+    pub const fn u(&self) -> &Vec3 {
+        &self.axis[0]
+    }
+
+    #[must_use]
+    pub const fn v(&self) -> &Vec3 {
+        &self.axis[1]
+    }
+
+    #[must_use]
+    pub const fn w(&self) -> &Vec3 {
+        &self.axis[2]
+    }
+
+    /// Transform from basis coordinates to world coordinates
+    #[must_use]
+    pub fn transform(&self, v: &Vec3) -> Vec3 {
+        (v[0] * self.u()) + (v[1] * self.v()) + (v[2] * self.w())
+    }
+
+    /// Transform from world coordinates to basis coordinates
+    #[must_use]
+    pub fn to_local(&self, v: &Vec3) -> Vec3 {
+        Vec3::new(v.dot(self.u()), v.dot(self.v()), v.dot(self.w()))
+    }
+}
@@ -0,0 +1,57 @@
+use crate::aabb::Aabb;
+use crate::hittable::{HitRecord, Hittable};
+use crate::interval::Interval;
+use crate::material::Material;
+use crate::onb::Onb;
+use crate::ray::Ray;
+use crate::vec3::{Point3, Vec3};
+
+/// Infinite plane through `point`. UVs are the distances along two fixed
+/// in-plane axes, so they grow without bound rather than wrapping.
+pub struct Plane {
+    point: Point3,
+    onb: Onb, // `w` is the plane normal
+    mat: Material,
+    bbox: Aabb,
+}
+
+impl Plane {
+    #[must_use]
+    pub fn new(point: Point3, normal: &Vec3, mat: Material) -> Self {
+        Self {
+            point,
+            onb: Onb::new(normal),
+            mat,
+            bbox: Aabb::universe(),
+        }
+    }
+}
+
+impl Hittable for Plane {
+    fn hit(&self, ray: &Ray, ray_t: &Interval) -> Option<HitRecord> {
+        let normal = self.onb.w();
+        let denom = normal.dot(ray.direction());
+
+        // No hit if the ray is parallel to the plane
+        if denom.abs() < 1e-8 {
+            return None;
+        }
+
+        let t = normal.dot(&(&self.point - ray.origin())) / denom;
+        if !ray_t.surrounds(t) {
+            return None;
+        }
+
+        let p = ray.at(t);
+        let local = self.onb.to_local(&(&p - &self.point));
+        let mut hit_record = HitRecord::new(p, Vec3::new(0., 0., 0.), self.mat.clone(), t);
+        hit_record.set_face_normal(ray, normal.clone());
+        hit_record.set_uv(local.x(), local.y());
+
+        Some(hit_record)
+    }
+
+    fn bounding_box(&self) -> &Aabb {
+        &self.bbox
+    }
+}
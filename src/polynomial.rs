@@ -0,0 +1,304 @@
+//! Real root finding for low-degree polynomials, used by analytic primitives
+
+use std::f64::consts::PI;
+
+/// Below this magnitude a value is treated as zero
+const EPSILON: f64 = 1e-12;
+
+/// How many times farther out than the others a root has to lie before it is
+/// beyond anything a scene can reach, see `drops_degree`
+const FAR_ROOT_RATIO: f64 = 1e20;
+
+/// Whether a polynomial whose three highest coefficients are `lead`, `next`
+/// and `after` can drop a degree.
+///
+/// A small `lead` adds a root near `-next / lead`, while the others stay around
+/// `after / next` in magnitude. The degree is only dropped when that extra root
+/// lies more than `FAR_ROOT_RATIO` times farther out, which neither scaling the
+/// equation nor translating the scene (and with it the roots) changes. With no
+/// `after` term there is nothing to compare against, so `lead` must vanish.
+fn drops_degree(lead: f64, next: f64, after: f64) -> bool {
+    lead == 0. || (after != 0. && (lead * after).abs() * FAR_ROOT_RATIO <= next * next)
+}
+
+/// Real roots of `a t^2 + b t + c`, in ascending order. Falls back to the
+/// linear equation when `a` vanishes, see `drops_degree`.
+#[must_use]
+pub fn solve_quadratic(a: f64, b: f64, c: f64) -> Vec<f64> {
+    if drops_degree(a, b, c) {
+        return if b == 0. { Vec::new() } else { vec![-c / b] };
+    }
+
+    let discriminant = b.mul_add(b, -4. * a * c);
+    if discriminant < 0. {
+        return Vec::new();
+    }
+
+    // Avoid cancellation between -b and the square root by computing the larger
+    // magnitude root first and deriving the other from the product of the roots
+    let q = -0.5 * b.signum().mul_add(discriminant.sqrt(), b);
+    let (r0, r1) = if q == 0. { (0., 0.) } else { (q / a, c / q) };
+    if r0 < r1 { vec![r0, r1] } else { vec![r1, r0] }
+}
+
+/// Real roots of `t^3 + a t^2 + b t + c`, in ascending order
+#[allow(clippy::many_single_char_names)]
+fn solve_normalized_cubic(a: f64, b: f64, c: f64) -> Vec<f64> {
+    // Substitute t = x - a/3 to get the depressed cubic x^3 + p x + q
+    let a_third = a / 3.;
+    let p = a.mul_add(-a_third, b);
+    let q = (2. * a_third * a_third).mul_add(a_third, -a_third * b) + c;
+
+    let half_q = q / 2.;
+    let third_p = p / 3.;
+    let discriminant = half_q.mul_add(half_q, third_p * third_p * third_p);
+
+    let mut roots = if discriminant.abs() < EPSILON {
+        if half_q.abs() < EPSILON {
+            // One triple root
+            vec![0.]
+        } else {
+            // One single and one double root
+            let u = (-half_q).cbrt();
+            vec![2. * u, -u]
+        }
+    } else if discriminant < 0. {
+        // Three distinct real roots, found with the trigonometric method
+        let phi = (-half_q / (-third_p * third_p * third_p).sqrt())
+            .clamp(-1., 1.)
+            .acos()
+            / 3.;
+        let m = 2. * (-third_p).sqrt();
+        vec![
+            m * phi.cos(),
+            m * (phi + 2. * PI / 3.).cos(),
+            m * (phi - 2. * PI / 3.).cos(),
+        ]
+    } else {
+        // One real root
+        let sqrt_d = discriminant.sqrt();
+        vec![(sqrt_d - half_q).cbrt() - (sqrt_d + half_q).cbrt()]
+    };
+
+    for root in &mut roots {
+        *root -= a_third;
+    }
+    roots.sort_by(f64::total_cmp);
+    roots
+}
+
+/// Real roots of `c4 t^4 + c3 t^3 + c2 t^2 + c1 t + c0`, in ascending order.
+///
+/// Solves through the resolvent cubic (Ferrari's method) and then polishes each
+/// root with Newton's method on the original polynomial, which recovers most of
+/// the precision lost to cancellation in the closed form.
+#[allow(clippy::many_single_char_names)]
+pub fn solve_quartic(c4: f64, c3: f64, c2: f64, c1: f64, c0: f64) -> Vec<f64> {
+    if drops_degree(c4, c3, c2) {
+        return solve_cubic(c3, c2, c1, c0);
+    }
+
+    // Normalize to t^4 + a t^3 + b t^2 + c t + d
+    let (a, b, c, d) = (c3 / c4, c2 / c4, c1 / c4, c0 / c4);
+
+    // Substitute t = x - a/4 to get the depressed quartic x^4 + p x^2 + q x + r
+    let a_sq = a * a;
+    let p = (-3. / 8_f64).mul_add(a_sq, b);
+    let q = (a_sq * a).mul_add(1. / 8., (-0.5 * a).mul_add(b, c));
+    let r = (-3. / 256. * a_sq).mul_add(a_sq, (a_sq / 16.).mul_add(b, (-0.25 * a).mul_add(c, d)));
+
+    let mut roots = if r.abs() < EPSILON {
+        // No constant term: x (x^3 + p x + q) = 0
+        let mut roots = solve_normalized_cubic(0., p, q);
+        roots.push(0.);
+        roots
+    } else {
+        // Take one real root of the resolvent cubic z^3 - p/2 z^2 - r z + (r p / 2 - q^2 / 8)
+        let z = *solve_normalized_cubic(-0.5 * p, -r, (0.5 * r).mul_add(p, -q * q / 8.))
+            .last()
+            .unwrap_or(&0.);
+
+        let u = z.mul_add(2., -p);
+        let v = z.mul_add(z, -r);
+        if u < -EPSILON || v < -EPSILON {
+            return Vec::new();
+        }
+        let u = u.max(0.).sqrt();
+        let v = v.max(0.).sqrt();
+        let v = if q < 0. { -v } else { v };
+
+        let mut roots = solve_quadratic(1., u, z - v);
+        roots.extend(solve_quadratic(1., -u, z + v));
+        roots
+    };
+
+    let shift = a / 4.;
+    for root in &mut roots {
+        *root = polish(*root - shift, [1., a, b, c, d]);
+    }
+    roots.sort_by(f64::total_cmp);
+    roots
+}
+
+/// Real roots of `a t^3 + b t^2 + c t + d`, in ascending order
+#[allow(clippy::many_single_char_names)]
+#[must_use]
+pub fn solve_cubic(a: f64, b: f64, c: f64, d: f64) -> Vec<f64> {
+    if drops_degree(a, b, c) {
+        return solve_quadratic(b, c, d);
+    }
+    solve_normalized_cubic(b / a, c / a, d / a)
+}
+
+/// Refines an approximate root of the quartic with the given coefficients,
+/// highest degree first, using a few Newton steps
+fn polish(mut t: f64, coefficients: [f64; 5]) -> f64 {
+    for _ in 0..4 {
+        let (value, derivative) =
+            coefficients
+                .iter()
+                .fold((0_f64, 0_f64), |(value, derivative), &coefficient| {
+                    (value.mul_add(t, coefficient), derivative.mul_add(t, value))
+                });
+        if derivative.abs() < EPSILON {
+            break;
+        }
+        let step = value / derivative;
+        t -= step;
+        if step.abs() <= 1e-14 * t.abs().max(1.) {
+            break;
+        }
+    }
+    t
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use super::*;
+    use crate::cylinder::Cylinder;
+    use crate::hittable::Hittable;
+    use crate::interval::Interval;
+    use crate::material::Material;
+    use crate::ray::Ray;
+    use crate::texture::SolidColor;
+    use crate::vec3::{Color, Point3, Vec3};
+
+    fn assert_roots(actual: &[f64], expected: &[f64], tolerance: f64) {
+        assert_eq!(
+            actual.len(),
+            expected.len(),
+            "found roots {actual:?}, expected {expected:?}"
+        );
+        for (root, expected_root) in actual.iter().zip(expected) {
+            assert!(
+                (root - expected_root).abs() <= tolerance,
+                "found roots {actual:?}, expected {expected:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn quadratic_roots() {
+        assert_roots(&solve_quadratic(1., -4., 3.), &[1., 3.], 1e-12);
+        assert_roots(&solve_quadratic(-2., 0., 8.), &[-2., 2.], 1e-12);
+        assert_roots(&solve_quadratic(1., -2., 1.), &[1., 1.], 1e-12);
+        assert_roots(&solve_quadratic(1., 0., 1.), &[], 0.);
+    }
+
+    #[test]
+    fn quadratic_avoids_cancellation() {
+        // The small root is lost to cancellation by the textbook formula
+        let roots = solve_quadratic(1., -1e8, 1.);
+        assert_roots(&roots, &[1e-8, 1e8], 1e-20);
+        assert!((roots[1] - 1e8).abs() < 1e-6);
+    }
+
+    #[test]
+    fn quadratic_falls_back_to_linear() {
+        assert_roots(&solve_quadratic(0., 2., -1.), &[0.5], 1e-12);
+        assert_roots(&solve_quadratic(1e-30, 2., -1.), &[0.5], 1e-12);
+        assert_roots(&solve_quadratic(0., 0., 1.), &[], 0.);
+        assert_roots(&solve_quadratic(0., 0., 0.), &[], 0.);
+    }
+
+    #[test]
+    fn quadratic_tolerance_is_relative() {
+        // Scaling an equation doesn't change its roots, however small it gets
+        assert_roots(&solve_quadratic(1e-20, -4e-20, 3e-20), &[1., 3.], 1e-12);
+        assert_roots(&solve_quadratic(1e20, -4e20, 3e20), &[1., 3.], 1e-12);
+    }
+
+    #[test]
+    fn quadratic_tolerance_is_translation_invariant() {
+        // A unit quadric two million units away: `a` is tiny next to `c`, but
+        // not next to the roots, and must not be dropped
+        assert_roots(
+            &solve_quadratic(1., -4e6, 4e12 - 1.),
+            &[2e6 - 1., 2e6 + 1.],
+            1e-6,
+        );
+        assert_roots(&solve_quadratic(1., -4e6, 4e12 + 1.), &[], 0.);
+    }
+
+    #[test]
+    fn far_cylinder_hits() {
+        // Open tube of radius 1 standing on the x axis, far from the camera
+        let cylinder = Cylinder::new(
+            Point3::new(2e6, -1., 0.),
+            &Vec3::new(0., 2., 0.),
+            1.,
+            Material::Lambertian {
+                albedo: Arc::new(SolidColor::new(Color::new(0.5, 0.5, 0.5))),
+            },
+        )
+        .open();
+        let ray_t = Interval::new(0.001, f64::INFINITY);
+
+        let ray = Ray::new(Point3::new(0., 0., 0.), Vec3::new(1., 0., 0.), 0.);
+        let hit = cylinder.hit(&ray, &ray_t).expect("ray should hit the tube");
+        assert!((hit.t() - (2e6 - 1.)).abs() < 1e-6, "hit at {}", hit.t());
+
+        // Passing five units beside it
+        let ray = Ray::new(Point3::new(0., 0., 5.), Vec3::new(1., 0., 0.), 0.);
+        assert!(cylinder.hit(&ray, &ray_t).is_none());
+    }
+
+    #[test]
+    fn cubic_roots() {
+        // (t - 1)(t - 2)(t - 3)
+        assert_roots(&solve_cubic(1., -6., 11., -6.), &[1., 2., 3.], 1e-12);
+        // (t - 2)(t^2 + 1)
+        assert_roots(&solve_cubic(1., -2., 1., -2.), &[2.], 1e-12);
+        // -2 (t + 1)(t - 0.5)(t - 4)
+        assert_roots(&solve_cubic(-2., 7., 5., -4.), &[-1., 0.5, 4.], 1e-12);
+        // (t - 1)^2 (t - 2), with the double root reported once
+        assert_roots(&solve_cubic(1., -4., 5., -2.), &[1., 2.], 1e-6);
+        // t^3
+        assert_roots(&solve_cubic(1., 0., 0., 0.), &[0.], 1e-12);
+        // Falls back to the quadratic (t - 1)(t - 3)
+        assert_roots(&solve_cubic(0., 1., -4., 3.), &[1., 3.], 1e-12);
+    }
+
+    #[test]
+    fn quartic_roots() {
+        // (t - 1)(t - 2)(t - 3)(t - 4)
+        assert_roots(
+            &solve_quartic(1., -10., 35., -50., 24.),
+            &[1., 2., 3., 4.],
+            1e-10,
+        );
+        // (t^2 - 1)(t^2 + 1)
+        assert_roots(&solve_quartic(1., 0., 0., 0., -1.), &[-1., 1.], 1e-12);
+        // 3 t (t - 1)(t + 2)(t - 5), with no constant term
+        assert_roots(
+            &solve_quartic(3., -12., -21., 30., 0.),
+            &[-2., 0., 1., 5.],
+            1e-10,
+        );
+        assert_roots(&solve_quartic(1., 0., 0., 0., 1.), &[], 0.);
+        // Falls back to the cubic (t - 1)(t - 2)(t - 3)
+        assert_roots(&solve_quartic(0., 1., -6., 11., -6.), &[1., 2., 3.], 1e-12);
+    }
+}
@@ -0,0 +1,117 @@
+use rand::Rng;
+
+use crate::aabb::Aabb;
+use crate::hittable::{HitRecord, Hittable, area_to_solid_angle_pdf};
+use crate::interval::Interval;
+use crate::material::Material;
+use crate::ray::Ray;
+use crate::vec3::{Point3, Vec3};
+
+/// Parallelogram spanned by the edge vectors `u` and `v` from corner `q`
+pub struct Quad {
+    q: Point3,
+    u: Vec3,
+    v: Vec3,
+    w: Vec3, // Cached n / (n . n), used to find planar coordinates of hit points
+    normal: Vec3,
+    d: f64, // Plane constant, such that normal . p = d for points on the plane
+    area: f64,
+    mat: Material,
+    bbox: Aabb,
+}
+
+impl Quad {
+    /// # Panics
+    ///
+    /// Panics if `u` and `v` are parallel or zero, as they then span no area.
+    #[allow(clippy::many_single_char_names)]
+    #[must_use]
+    pub fn new(q: Point3, u: Vec3, v: Vec3, mat: Material) -> Self {
+        let n = u.cross(&v);
+        assert!(
+            n.length_squared() > 0.,
+            "Quad edge vectors must not be parallel"
+        );
+        let normal = n.unit();
+        let d = normal.dot(&q);
+        let w = &n / n.dot(&n);
+        let area = n.length();
+
+        // Compute the bounding box of all four vertices
+        let bbox_diagonal1 = Aabb::from_points(&q, &(&q + &u + &v));
+        let bbox_diagonal2 = Aabb::from_points(&(&q + &u), &(&q + &v));
+        let bbox = Aabb::union(&bbox_diagonal1, &bbox_diagonal2);
+
+        Self {
+            q,
+            u,
+            v,
+            w,
+            normal,
+            d,
+            area,
+            mat,
+            bbox,
+        }
+    }
+
+    /// Returns true if the planar coordinates `(a, b)` lie inside the quad
+    fn is_interior(a: f64, b: f64) -> bool {
+        let unit_interval = Interval::new(0., 1.);
+        unit_interval.contains(a) && unit_interval.contains(b)
+    }
+}
+
+impl Hittable for Quad {
+    fn hit(&self, ray: &Ray, ray_t: &Interval) -> Option<HitRecord> {
+        let denom = self.normal.dot(ray.direction());
+
+        // No hit if the ray is parallel to the plane
+        if denom.abs() < 1e-8 {
+            return None;
+        }
+
+        // No hit if the hit point parameter t is outside the ray interval
+        let t = (self.d - self.normal.dot(ray.origin())) / denom;
+        if !ray_t.contains(t) {
+            return None;
+        }
+
+        // Determine if the hit point lies within the planar shape using its plane coordinates
+        let intersection = ray.at(t);
+        let planar_hit_vector = &intersection - &self.q;
+        let alpha = self.w.dot(&planar_hit_vector.cross(&self.v));
+        let beta = self.w.dot(&self.u.cross(&planar_hit_vector));
+
+        if !Self::is_interior(alpha, beta) {
+            return None;
+        }
+
+        let mut hit_record =
+            HitRecord::new(intersection, Vec3::new(0., 0., 0.), self.mat.clone(), t);
+        hit_record.set_face_normal(ray, self.normal.clone());
+        hit_record.set_uv(alpha, beta);
+
+        Some(hit_record)
+    }
+
+    fn bounding_box(&self) -> &Aabb {
+        &self.bbox
+    }
+
+    fn sample(&self, origin: &Point3, time: f64) -> Option<(Vec3, f64)> {
+        let mut rng = rand::thread_rng();
+        let point = &self.q + (rng.gen::<f64>() * &self.u) + (rng.gen::<f64>() * &self.v);
+        let direction = point - origin;
+        let pdf = self.pdf(origin, &direction, time);
+        (pdf > 0.).then_some((direction, pdf))
+    }
+
+    fn pdf(&self, origin: &Point3, direction: &Vec3, time: f64) -> f64 {
+        let ray = Ray::new(origin.clone(), direction.clone(), time);
+        self.hit(&ray, &Interval::new(0.001, f64::INFINITY))
+            .map_or(0., |hit_record| {
+                area_to_solid_angle_pdf(&ray, &hit_record, self.area)
+            })
+    }
+}
@@ -1,26 +1,38 @@
 use crate::vec3::{Point3, Vec3};
 
+#[derive(Clone)]
 pub struct Ray {
     orig: Point3,
     dir: Vec3,
+    tm: f64, // Moment during the exposure at which the ray was cast
 }
 
 impl Ray {
-    pub const fn new(origin: Point3, direction: Vec3) -> Self {
+    #[must_use]
+    pub const fn new(origin: Point3, direction: Vec3, time: f64) -> Self {
         Self {
             orig: origin,
             dir: direction,
+            tm: time,
         }
     }
 
+    #[must_use]
     pub const fn origin(&self) -> &Point3 {
         &self.orig
     }
 
+    #[must_use]
     pub const fn direction(&self) -> &Vec3 {
         &self.dir
     }
 
+    #[must_use]
+    pub const fn time(&self) -> f64 {
+        self.tm
+    }
+
+    #[must_use]
     pub fn at(&self, t: f64) -> Point3 {
         self.origin() + self.direction() * t
     }
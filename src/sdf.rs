@@ -0,0 +1,322 @@
+//! Signed distance fields, rendered by sphere tracing
+//!
+//! A signed distance field gives, for any point, the distance to the nearest
+//! surface: positive outside, negative inside. Marching a ray forward by that
+//! distance can never overshoot the surface, which lets us intersect shapes
+//! that have no closed-form ray equation.
+
+use crate::aabb::Aabb;
+use crate::hittable::{HitRecord, Hittable};
+use crate::interval::Interval;
+use crate::material::Material;
+use crate::ray::Ray;
+use crate::vec3::{Point3, Vec3};
+
+/// A signed distance function. Implemented for closures, so any
+/// `Fn(&Point3) -> f64` can be used directly.
+pub trait Sdf: Send + Sync {
+    fn distance(&self, p: &Point3) -> f64;
+}
+
+impl<F> Sdf for F
+where
+    F: Fn(&Point3) -> f64 + Send + Sync,
+{
+    fn distance(&self, p: &Point3) -> f64 {
+        self(p)
+    }
+}
+
+/// Composable tree of distance field primitives and operators
+pub enum SdfNode {
+    Sphere {
+        center: Point3,
+        radius: f64,
+    },
+    Cuboid {
+        center: Point3,
+        half_extents: Vec3,
+    },
+    /// Torus around the y axis through the origin
+    Torus {
+        major_radius: f64,
+        minor_radius: f64,
+    },
+    /// Power-n Mandelbulb fractal, roughly filling the sphere of radius 1.2
+    Mandelbulb {
+        power: f64,
+        iterations: u32,
+    },
+    Union(Box<Self>, Box<Self>),
+    Intersection(Box<Self>, Box<Self>),
+    Difference(Box<Self>, Box<Self>),
+    /// Union that blends the two shapes together over a distance of about `k`
+    SmoothUnion {
+        a: Box<Self>,
+        b: Box<Self>,
+        k: f64,
+    },
+    /// Infinite repetition with the given cell size per axis; zero disables an axis
+    Repeat {
+        node: Box<Self>,
+        period: Vec3,
+    },
+    /// Rotation about the y axis by `rate` radians per unit of height
+    Twist {
+        node: Box<Self>,
+        rate: f64,
+    },
+    Translate {
+        node: Box<Self>,
+        offset: Vec3,
+    },
+}
+
+impl SdfNode {
+    #[must_use]
+    pub fn union(self, other: Self) -> Self {
+        Self::Union(Box::new(self), Box::new(other))
+    }
+
+    #[must_use]
+    pub fn intersection(self, other: Self) -> Self {
+        Self::Intersection(Box::new(self), Box::new(other))
+    }
+
+    #[must_use]
+    pub fn difference(self, other: Self) -> Self {
+        Self::Difference(Box::new(self), Box::new(other))
+    }
+
+    #[must_use]
+    pub fn smooth_union(self, other: Self, k: f64) -> Self {
+        Self::SmoothUnion {
+            a: Box::new(self),
+            b: Box::new(other),
+            k,
+        }
+    }
+
+    #[must_use]
+    pub fn repeat(self, period: Vec3) -> Self {
+        Self::Repeat {
+            node: Box::new(self),
+            period,
+        }
+    }
+
+    #[must_use]
+    pub fn twist(self, rate: f64) -> Self {
+        Self::Twist {
+            node: Box::new(self),
+            rate,
+        }
+    }
+
+    #[must_use]
+    pub fn translate(self, offset: Vec3) -> Self {
+        Self::Translate {
+            node: Box::new(self),
+            offset,
+        }
+    }
+}
+
+impl Sdf for SdfNode {
+    fn distance(&self, p: &Point3) -> f64 {
+        match self {
+            Self::Sphere { center, radius } => (p - center).length() - radius,
+
+            Self::Cuboid {
+                center,
+                half_extents,
+            } => {
+                let q = Vec3::new(
+                    (p.x() - center.x()).abs() - half_extents.x(),
+                    (p.y() - center.y()).abs() - half_extents.y(),
+                    (p.z() - center.z()).abs() - half_extents.z(),
+                );
+                let outside = Vec3::new(q.x().max(0.), q.y().max(0.), q.z().max(0.)).length();
+                let inside = q.x().max(q.y()).max(q.z()).min(0.);
+                outside + inside
+            }
+
+            Self::Torus {
+                major_radius,
+                minor_radius,
+            } => (p.x().hypot(p.z()) - major_radius).hypot(p.y()) - minor_radius,
+
+            Self::Mandelbulb { power, iterations } => mandelbulb(p, *power, *iterations),
+
+            Self::Union(a, b) => a.distance(p).min(b.distance(p)),
+            Self::Intersection(a, b) => a.distance(p).max(b.distance(p)),
+            Self::Difference(a, b) => a.distance(p).max(-b.distance(p)),
+
+            Self::SmoothUnion { a, b, k } => {
+                // Polynomial smooth minimum
+                let (da, db) = (a.distance(p), b.distance(p));
+                let h = (0.5 + 0.5 * (db - da) / k).clamp(0., 1.);
+                (k * h).mul_add(h - 1., (da - db).mul_add(h, db))
+            }
+
+            Self::Repeat { node, period } => {
+                let wrap = |x: f64, period: f64| {
+                    if period > 0. {
+                        period.mul_add(-(x / period).round(), x)
+                    } else {
+                        x
+                    }
+                };
+                node.distance(&Point3::new(
+                    wrap(p.x(), period.x()),
+                    wrap(p.y(), period.y()),
+                    wrap(p.z(), period.z()),
+                ))
+            }
+
+            Self::Twist { node, rate } => {
+                let (sin, cos) = (rate * p.y()).sin_cos();
+                node.distance(&Point3::new(
+                    cos.mul_add(p.x(), -sin * p.z()),
+                    p.y(),
+                    sin.mul_add(p.x(), cos * p.z()),
+                ))
+            }
+
+            Self::Translate { node, offset } => node.distance(&(p - offset)),
+        }
+    }
+}
+
+/// Distance estimate for the Mandelbulb, from the running derivative of the
+/// escape-time iteration
+fn mandelbulb(p: &Point3, power: f64, iterations: u32) -> f64 {
+    let mut z = p.clone();
+    let mut dr = 1.;
+    let mut r = 0.;
+
+    for _ in 0..iterations {
+        r = z.length();
+        if r > 2. || r == 0. {
+            break;
+        }
+
+        // Raise z to the given power in spherical coordinates
+        let theta = (z.z() / r).acos() * power;
+        let phi = z.y().atan2(z.x()) * power;
+        dr = (r.powf(power - 1.) * power).mul_add(dr, 1.);
+
+        let zr = r.powf(power);
+        z =
+            zr * Vec3::new(
+                theta.sin() * phi.cos(),
+                phi.sin() * theta.sin(),
+                theta.cos(),
+            ) + p;
+    }
+
+    if r == 0. {
+        return 0.;
+    }
+    0.5 * r.ln() * r / dr
+}
+
+/// Hittable surface defined by a signed distance field, intersected by
+/// sphere tracing. Distance fields can be unbounded, so the region to march
+/// through is given explicitly.
+pub struct SdfHittable {
+    sdf: Box<dyn Sdf>,
+    mat: Material,
+    bbox: Aabb,
+    max_steps: u32,
+    epsilon: f64,    // Distance at which a march counts as a hit
+    step_scale: f64, // Fraction of the distance to advance each step
+}
+
+impl SdfHittable {
+    #[must_use]
+    pub fn new(sdf: Box<dyn Sdf>, bounds: Aabb, mat: Material) -> Self {
+        Self {
+            sdf,
+            mat,
+            bbox: bounds,
+            max_steps: 256,
+            epsilon: 1e-4,
+            step_scale: 1.,
+        }
+    }
+
+    #[must_use]
+    pub const fn max_steps(mut self, max_steps: u32) -> Self {
+        self.max_steps = max_steps;
+        self
+    }
+
+    #[must_use]
+    pub const fn epsilon(mut self, epsilon: f64) -> Self {
+        self.epsilon = epsilon;
+        self
+    }
+
+    /// Advance by only this fraction of the distance each step. Operators such as
+    /// twist distort distances, and values below 1 keep the march from overshooting.
+    #[must_use]
+    pub const fn step_scale(mut self, step_scale: f64) -> Self {
+        self.step_scale = step_scale;
+        self
+    }
+
+    /// Estimates the outward normal at `p` by central differences of the field
+    fn normal(&self, p: &Point3) -> Vec3 {
+        let h = self.epsilon;
+        let gradient = |axis: Vec3| {
+            self.sdf.distance(&(p + &(h * &axis))) - self.sdf.distance(&(p - &(h * &axis)))
+        };
+        Vec3::new(
+            gradient(Vec3::new(1., 0., 0.)),
+            gradient(Vec3::new(0., 1., 0.)),
+            gradient(Vec3::new(0., 0., 1.)),
+        )
+        .unit()
+    }
+}
+
+impl Hittable for SdfHittable {
+    fn hit(&self, ray: &Ray, ray_t: &Interval) -> Option<HitRecord> {
+        let march_t = self.bbox.hit_interval(ray, ray_t)?;
+        let ray_length = ray.direction().length();
+
+        let mut t = march_t.min();
+        for _ in 0..self.max_steps {
+            if t > march_t.max() {
+                return None;
+            }
+
+            let p = ray.at(t);
+            // Distances are negative inside, so rays leaving a solid march the same way
+            let distance = self.sdf.distance(&p).abs();
+            if distance < self.epsilon {
+                if ray_t.surrounds(t) {
+                    let outward_normal = self.normal(&p);
+                    let mut hit_record =
+                        HitRecord::new(p, Vec3::new(0., 0., 0.), self.mat.clone(), t);
+                    hit_record.set_face_normal(ray, outward_normal);
+                    return Some(hit_record);
+                }
+
+                // Converged on a surface before the start of `ray_t`, such as the
+                // one the ray just left, so step clear of it and keep marching
+                t += self.epsilon / ray_length;
+                continue;
+            }
+
+            t += self.step_scale * distance / ray_length;
+        }
+
+        None
+    }
+
+    fn bounding_box(&self) -> &Aabb {
+        &self.bbox
+    }
+}
@@ -0,0 +1,324 @@
+//! Analytic daylight from the Preetham sky model
+//!
+//! A. J. Preetham, P. Shirley and B. Smits, "A Practical Analytic Model for
+//! Daylight", SIGGRAPH 1999. Sky luminance and chromaticity follow the Perez
+//! distribution fitted to the turbidity and the position of the sun, giving a
+//! sky that whitens near the sun and the horizon and reddens at sunset.
+
+use std::f64::consts::PI;
+
+use rand::Rng;
+
+use crate::background::Background;
+use crate::onb::Onb;
+use crate::vec3::{Color, Vec3};
+
+/// Angular radius of the sun seen from the earth
+const SUN_ANGULAR_RADIUS: f64 = 0.004_65;
+
+/// Luminance of the solar disk before atmospheric extinction, in the same
+/// kcd/m² as the sky
+const SUN_LUMINANCE: f64 = 1.6e6;
+
+/// Coefficients A to E of the Perez distribution, as linear functions of turbidity
+const PEREZ_LUMINANCE: [[f64; 2]; 5] = [
+    [0.1787, -1.4630],
+    [-0.3554, 0.4275],
+    [-0.0227, 5.3251],
+    [0.1206, -2.5771],
+    [-0.0670, 0.3703],
+];
+const PEREZ_X: [[f64; 2]; 5] = [
+    [-0.0193, -0.2592],
+    [-0.0665, 0.0008],
+    [-0.0004, 0.2125],
+    [-0.0641, -0.8989],
+    [-0.0033, 0.0452],
+];
+const PEREZ_Y: [[f64; 2]; 5] = [
+    [-0.0167, -0.2608],
+    [-0.0950, 0.0092],
+    [-0.0079, 0.2102],
+    [-0.0441, -1.6537],
+    [-0.0109, 0.0529],
+];
+
+/// Zenith chromaticity as a polynomial in turbidity (rows: T², T, 1) and the
+/// sun's zenith angle (columns: θ³, θ², θ, 1)
+const ZENITH_X: [[f64; 4]; 3] = [
+    [0.00166, -0.00375, 0.00209, 0.],
+    [-0.02903, 0.06377, -0.03202, 0.00394],
+    [0.11693, -0.21196, 0.06052, 0.25886],
+];
+const ZENITH_Y: [[f64; 4]; 3] = [
+    [0.00275, -0.00610, 0.00317, 0.],
+    [-0.04214, 0.08970, -0.04153, 0.00516],
+    [0.15346, -0.26756, 0.06670, 0.26688],
+];
+
+/// One channel of the sky: its value at the zenith and Perez coefficients
+struct PerezChannel {
+    coefficients: [f64; 5],
+    normalized_zenith: f64, // Zenith value divided by the distribution at the zenith
+}
+
+impl PerezChannel {
+    fn new(table: &[[f64; 2]; 5], turbidity: f64, zenith: f64, sun_theta: f64) -> Self {
+        let coefficients = table.map(|[slope, offset]| slope.mul_add(turbidity, offset));
+        let at_zenith = perez(&coefficients, 1., sun_theta, sun_theta.cos());
+        Self {
+            coefficients,
+            normalized_zenith: zenith / at_zenith,
+        }
+    }
+
+    fn value(&self, cos_theta: f64, gamma: f64, cos_gamma: f64) -> f64 {
+        self.normalized_zenith * perez(&self.coefficients, cos_theta, gamma, cos_gamma)
+    }
+}
+
+/// The Perez sky distribution at zenith angle θ and angle γ from the sun
+#[allow(clippy::many_single_char_names)]
+fn perez([a, b, c, d, e]: &[f64; 5], cos_theta: f64, gamma: f64, cos_gamma: f64) -> f64 {
+    (1. + a * (b / cos_theta).exp())
+        * (e * cos_gamma).mul_add(cos_gamma, c.mul_add((d * gamma).exp(), 1.))
+}
+
+fn zenith_chromaticity(table: &[[f64; 4]; 3], turbidity: f64, sun_theta: f64) -> f64 {
+    let turbidity_terms = [turbidity * turbidity, turbidity, 1.];
+    let theta_terms = [sun_theta.powi(3), sun_theta.powi(2), sun_theta, 1.];
+    table
+        .iter()
+        .zip(turbidity_terms)
+        .map(|(row, t)| t * row.iter().zip(theta_terms).map(|(c, s)| c * s).sum::<f64>())
+        .sum()
+}
+
+/// Linear sRGB color of CIE xyY chromaticity and luminance
+fn xyy_to_rgb(x: f64, y: f64, luminance: f64) -> Color {
+    let big_x = x / y * luminance;
+    let big_z = (1. - x - y) / y * luminance;
+    Color::new(
+        3.2406_f64.mul_add(big_x, (-1.5372_f64).mul_add(luminance, -0.4986 * big_z)),
+        (-0.9689_f64).mul_add(big_x, 1.8758_f64.mul_add(luminance, 0.0415 * big_z)),
+        0.0557_f64.mul_add(big_x, (-0.2040_f64).mul_add(luminance, 1.0570 * big_z)),
+    )
+}
+
+/// Physically based daylight: the Preetham sky, the solar disk and a diffuse
+/// ground plane below the horizon lit by both
+pub struct Sky {
+    luminance: PerezChannel,
+    chromaticity_x: PerezChannel,
+    chromaticity_y: PerezChannel,
+    sun_direction: Vec3,
+    sun_radiance: Color,
+    cos_sun_radius: f64,
+    ground_radiance: Color,
+    intensity: f64,
+}
+
+impl Sky {
+    #[must_use]
+    pub const fn builder() -> SkyBuilder {
+        SkyBuilder::default()
+    }
+
+    /// Sky radiance, ignoring the sun itself, from a direction above the horizon
+    fn sky_radiance(&self, direction: &Vec3) -> Color {
+        // The distribution blows up exactly at the horizon
+        let cos_theta = direction.y().max(0.01);
+        let cos_gamma = direction.dot(&self.sun_direction).clamp(-1., 1.);
+        let gamma = cos_gamma.acos();
+
+        let luminance = self.luminance.value(cos_theta, gamma, cos_gamma);
+        let x = self.chromaticity_x.value(cos_theta, gamma, cos_gamma);
+        let y = self.chromaticity_y.value(cos_theta, gamma, cos_gamma);
+        let rgb = xyy_to_rgb(x, y, luminance);
+        Color::new(rgb.x().max(0.), rgb.y().max(0.), rgb.z().max(0.))
+    }
+
+    /// Irradiance on a horizontal surface from the sky and the sun
+    fn ground_irradiance(&self) -> Color {
+        const THETA_STEPS: u32 = 32;
+        const PHI_STEPS: u32 = 64;
+        let d_theta = PI / 2. / f64::from(THETA_STEPS);
+        let d_phi = 2. * PI / f64::from(PHI_STEPS);
+
+        let mut irradiance = Color::new(0., 0., 0.);
+        for i in 0..THETA_STEPS {
+            let theta = (f64::from(i) + 0.5) * d_theta;
+            let (sin_theta, cos_theta) = theta.sin_cos();
+            for j in 0..PHI_STEPS {
+                let phi = (f64::from(j) + 0.5) * d_phi;
+                let direction = Vec3::new(sin_theta * phi.cos(), cos_theta, sin_theta * phi.sin());
+                irradiance +=
+                    cos_theta * sin_theta * d_theta * d_phi * self.sky_radiance(&direction);
+            }
+        }
+
+        let sun_solid_angle = 2. * PI * (1. - self.cos_sun_radius);
+        irradiance + (sun_solid_angle * self.sun_direction.y().max(0.)) * &self.sun_radiance
+    }
+}
+
+impl Background for Sky {
+    fn color(&self, direction: &Vec3) -> Color {
+        if direction.y() < 0. {
+            return self.intensity * &self.ground_radiance;
+        }
+
+        let mut radiance = self.sky_radiance(direction);
+        if direction.dot(&self.sun_direction) >= self.cos_sun_radius {
+            radiance += self.sun_radiance.clone();
+        }
+        self.intensity * radiance
+    }
+
+    fn is_samplable(&self) -> bool {
+        true
+    }
+
+    /// Samples the solar disk uniformly. The rest of the sky is smooth enough
+    /// to be found by ordinary bounces.
+    fn sample(&self) -> Option<(Vec3, f64)> {
+        let mut rng = rand::thread_rng();
+        let cos_theta = rng.gen::<f64>().mul_add(self.cos_sun_radius - 1., 1.);
+        let sin_theta = cos_theta.mul_add(-cos_theta, 1.).max(0.).sqrt();
+        let phi = 2. * PI * rng.gen::<f64>();
+
+        let local = Vec3::new(phi.cos() * sin_theta, phi.sin() * sin_theta, cos_theta);
+        let direction = Onb::new(&self.sun_direction).transform(&local);
+        Some((direction, self.pdf(&self.sun_direction)))
+    }
+
+    fn pdf(&self, direction: &Vec3) -> f64 {
+        if direction.dot(&self.sun_direction) >= self.cos_sun_radius {
+            1. / (2. * PI * (1. - self.cos_sun_radius))
+        } else {
+            0.
+        }
+    }
+}
+
+pub struct SkyBuilder {
+    turbidity: f64,       // Haziness, from 2 for a very clear sky to about 10 for haze
+    ground_albedo: Color, // Reflectance of the ground below the horizon
+    sun_elevation: f64,   // Degrees above the horizon
+    sun_azimuth: f64,     // Degrees from -z towards +x
+    intensity: f64,       // Scale from kcd/m² to scene radiance
+}
+
+impl SkyBuilder {
+    #[must_use]
+    pub const fn default() -> Self {
+        Self {
+            turbidity: 3.,
+            ground_albedo: Color::new(0.3, 0.3, 0.3),
+            sun_elevation: 45.,
+            sun_azimuth: 0.,
+            intensity: 0.05,
+        }
+    }
+
+    #[must_use]
+    pub fn build(self) -> Sky {
+        // The model only covers daylight, with the sun above the horizon
+        let elevation = self.sun_elevation.clamp(0., 90.).to_radians();
+        let azimuth = self.sun_azimuth.to_radians();
+        let sun_direction = Vec3::new(
+            elevation.cos() * azimuth.sin(),
+            elevation.sin(),
+            -elevation.cos() * azimuth.cos(),
+        );
+        let sun_theta = PI / 2. - elevation;
+        let turbidity = self.turbidity;
+
+        let chi = (4. / 9. - turbidity / 120.) * 2_f64.mul_add(-sun_theta, PI);
+        let zenith_luminance = 4.0453_f64
+            .mul_add(turbidity, -4.9710)
+            .mul_add(chi.tan(), (-0.2155_f64).mul_add(turbidity, 2.4192));
+
+        let mut sky = Sky {
+            luminance: PerezChannel::new(&PEREZ_LUMINANCE, turbidity, zenith_luminance, sun_theta),
+            chromaticity_x: PerezChannel::new(
+                &PEREZ_X,
+                turbidity,
+                zenith_chromaticity(&ZENITH_X, turbidity, sun_theta),
+                sun_theta,
+            ),
+            chromaticity_y: PerezChannel::new(
+                &PEREZ_Y,
+                turbidity,
+                zenith_chromaticity(&ZENITH_Y, turbidity, sun_theta),
+                sun_theta,
+            ),
+            sun_direction,
+            sun_radiance: Self::sun_radiance(turbidity, sun_theta),
+            cos_sun_radius: SUN_ANGULAR_RADIUS.cos(),
+            ground_radiance: Color::new(0., 0., 0.),
+            intensity: self.intensity,
+        };
+
+        sky.ground_radiance = (1. / PI) * (self.ground_albedo * sky.ground_irradiance());
+        sky
+    }
+
+    /// Sunlight after Rayleigh and aerosol extinction along its path through
+    /// the atmosphere, evaluated at a representative wavelength per channel
+    fn sun_radiance(turbidity: f64, sun_theta: f64) -> Color {
+        // Relative optical air mass, accounting for the curvature of the atmosphere
+        let zenith_degrees = sun_theta.to_degrees();
+        let air_mass =
+            1. / 0.15_f64.mul_add((93.885 - zenith_degrees).powf(-1.253), sun_theta.cos());
+
+        let angstrom_beta = 0.04608_f64.mul_add(turbidity, -0.04586);
+        let transmittance = |wavelength_um: f64| {
+            let rayleigh = 0.008_735 * wavelength_um.powf(-4.08);
+            let aerosol = angstrom_beta * wavelength_um.powf(-1.3);
+            (-(rayleigh + aerosol) * air_mass).exp()
+        };
+
+        SUN_LUMINANCE
+            * Color::new(
+                transmittance(0.68),
+                transmittance(0.55),
+                transmittance(0.44),
+            )
+    }
+
+    /// Haziness of the atmosphere, from 2 for a very clear sky to about 10 for haze
+    #[must_use]
+    pub const fn turbidity(mut self, turbidity: f64) -> Self {
+        self.turbidity = turbidity;
+        self
+    }
+
+    #[must_use]
+    pub const fn ground_albedo(mut self, ground_albedo: Color) -> Self {
+        self.ground_albedo = ground_albedo;
+        self
+    }
+
+    /// Degrees above the horizon. The model covers daylight only, so the sun
+    /// is kept between the horizon and the zenith.
+    #[must_use]
+    pub const fn sun_elevation(mut self, degrees: f64) -> Self {
+        self.sun_elevation = degrees;
+        self
+    }
+
+    /// Degrees from -z towards +x, seen from above
+    #[must_use]
+    pub const fn sun_azimuth(mut self, degrees: f64) -> Self {
+        self.sun_azimuth = degrees;
+        self
+    }
+
+    /// Scale from the model's kcd/m² to scene radiance
+    #[must_use]
+    pub const fn intensity(mut self, intensity: f64) -> Self {
+        self.intensity = intensity;
+        self
+    }
+}
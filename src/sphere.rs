@@ -1,36 +1,75 @@
+use std::f64::consts::PI;
+
+use rand::Rng;
+
+use crate::aabb::Aabb;
 use crate::hittable::{HitRecord, Hittable};
 use crate::interval::Interval;
 use crate::material::Material;
+use crate::onb::Onb;
 use crate::ray::Ray;
 use crate::vec3::{Point3, Vec3};
 
 pub struct Sphere {
-    center: Point3,
+    center: Ray, // Position at time 0, moving by `direction` per unit of time
     radius: f64,
     mat: Material,
+    bbox: Aabb,
 }
 
 impl Sphere {
+    /// Stationary sphere
+    #[must_use]
     pub fn new(center: Point3, radius: f64, mat: Material) -> Self {
+        Self::moving(center.clone(), center, radius, mat)
+    }
+
+    /// Sphere moving in a straight line, centered at `center1` at time 0 and
+    /// at `center2` at time 1. Its bounding box only covers that time span, so
+    /// the camera shutter should stay within it.
+    #[must_use]
+    pub fn moving(center1: Point3, center2: Point3, radius: f64, mat: Material) -> Self {
+        let radius = radius.max(0.0);
+        let r_vec = Vec3::new(radius, radius, radius);
+        let box1 = Aabb::from_points(&(&center1 - &r_vec), &(&center1 + &r_vec));
+        let box2 = Aabb::from_points(&(&center2 - &r_vec), &(&center2 + &r_vec));
+        let direction = center2 - &center1;
         Self {
-            center,
-            radius: radius.max(0.0),
+            center: Ray::new(center1, direction, 0.),
+            radius,
             mat,
+            bbox: Aabb::union(&box1, &box2),
         }
     }
 
-    const fn center(&self) -> &Point3 {
-        &self.center
+    fn center(&self, time: f64) -> Point3 {
+        self.center.at(time)
     }
 
     const fn radius(&self) -> &f64 {
         &self.radius
     }
+
+    /// Cosine of the half-angle of the cone the sphere subtends from a point
+    /// `distance_squared` away from its center, or `None` from inside it
+    fn cos_theta_max(&self, distance_squared: f64) -> Option<f64> {
+        let sin_squared = self.radius().powi(2) / distance_squared;
+        (sin_squared < 1.).then(|| (1. - sin_squared).sqrt())
+    }
+
+    /// Texture coordinates of a point on the unit sphere: `u` is the angle
+    /// around the y axis from x = -1, `v` the angle from y = -1, both scaled to [0, 1]
+    fn uv(p: &Point3) -> (f64, f64) {
+        let theta = (-p.y()).acos();
+        let phi = (-p.z()).atan2(p.x()) + PI;
+        (phi / (2. * PI), theta / PI)
+    }
 }
 
 impl Hittable for Sphere {
     fn hit(&self, ray: &Ray, ray_t: &Interval) -> Option<HitRecord> {
-        let oc = self.center() - ray.origin();
+        let current_center = self.center(ray.time());
+        let oc = &current_center - ray.origin();
         let a = ray.direction().length_squared();
         let half_b = ray.direction().dot(&oc);
         let c = oc.length_squared() - self.radius().powi(2);
@@ -53,10 +92,45 @@ impl Hittable for Sphere {
 
         let t = root;
         let p = ray.at(t);
-        let outward_normal = (&p - self.center()) / *self.radius();
+        let outward_normal = (&p - &current_center) / *self.radius();
         let mut hit_record = HitRecord::new(p, Vec3::new(0., 0., 0.), self.mat.clone(), t);
+        let uv = Self::uv(&outward_normal);
         hit_record.set_face_normal(ray, outward_normal);
+        hit_record.set_uv(uv.0, uv.1);
 
         Some(hit_record)
     }
+
+    fn bounding_box(&self) -> &Aabb {
+        &self.bbox
+    }
+
+    /// Samples directions uniformly within the cone the sphere subtends at `time`
+    fn sample(&self, origin: &Point3, time: f64) -> Option<(Vec3, f64)> {
+        let to_center = self.center(time) - origin;
+        let cos_theta_max = self.cos_theta_max(to_center.length_squared())?;
+
+        let mut rng = rand::thread_rng();
+        let cos_theta = rng.gen::<f64>().mul_add(cos_theta_max - 1., 1.);
+        let sin_theta = cos_theta.mul_add(-cos_theta, 1.).max(0.).sqrt();
+        let phi = 2. * PI * rng.gen::<f64>();
+        let local = Vec3::new(phi.cos() * sin_theta, phi.sin() * sin_theta, cos_theta);
+
+        let direction = Onb::new(&to_center).transform(&local);
+        Some((direction, 1. / (2. * PI * (1. - cos_theta_max))))
+    }
+
+    fn pdf(&self, origin: &Point3, direction: &Vec3, time: f64) -> f64 {
+        let ray = Ray::new(origin.clone(), direction.clone(), time);
+        if self
+            .hit(&ray, &Interval::new(0.001, f64::INFINITY))
+            .is_none()
+        {
+            return 0.;
+        }
+
+        let distance_squared = (self.center(time) - origin).length_squared();
+        self.cos_theta_max(distance_squared)
+            .map_or(0., |cos_theta_max| 1. / (2. * PI * (1. - cos_theta_max)))
+    }
 }
@@ -0,0 +1,212 @@
+use std::sync::Arc;
+
+use crate::noise::{Perlin, Worley};
+use crate::vec3::{Color, Point3};
+
+/// Spatially varying color, looked up at the surface coordinates `(u, v)` and
+/// the hit point `p`
+pub trait Texture: Send + Sync {
+    fn value(&self, u: f64, v: f64, p: &Point3) -> Color;
+}
+
+/// The same color everywhere
+pub struct SolidColor {
+    albedo: Color,
+}
+
+impl SolidColor {
+    #[must_use]
+    pub const fn new(albedo: Color) -> Self {
+        Self { albedo }
+    }
+}
+
+impl Texture for SolidColor {
+    fn value(&self, _u: f64, _v: f64, _p: &Point3) -> Color {
+        self.albedo.clone()
+    }
+}
+
+/// Checkerboard of solid cubes in space, alternating between two textures.
+/// Works on any surface, with or without texture coordinates.
+pub struct CheckerTexture {
+    inv_scale: f64,
+    even: Arc<dyn Texture>,
+    odd: Arc<dyn Texture>,
+}
+
+impl CheckerTexture {
+    /// Checker with cubes `scale` units across
+    pub fn new(scale: f64, even: Arc<dyn Texture>, odd: Arc<dyn Texture>) -> Self {
+        Self {
+            inv_scale: 1. / scale,
+            even,
+            odd,
+        }
+    }
+}
+
+impl Texture for CheckerTexture {
+    #[allow(clippy::cast_possible_truncation)]
+    fn value(&self, u: f64, v: f64, p: &Point3) -> Color {
+        let cell = |coord: f64| (self.inv_scale * coord).floor() as i64;
+
+        if (cell(p.x()) + cell(p.y()) + cell(p.z())) % 2 == 0 {
+            self.even.value(u, v, p)
+        } else {
+            self.odd.value(u, v, p)
+        }
+    }
+}
+
+/// Checkerboard in texture space, with `columns` by `rows` squares over the
+/// unit square of `(u, v)`
+pub struct UvCheckerTexture {
+    columns: f64,
+    rows: f64,
+    even: Arc<dyn Texture>,
+    odd: Arc<dyn Texture>,
+}
+
+impl UvCheckerTexture {
+    pub fn new(columns: f64, rows: f64, even: Arc<dyn Texture>, odd: Arc<dyn Texture>) -> Self {
+        Self {
+            columns,
+            rows,
+            even,
+            odd,
+        }
+    }
+}
+
+impl Texture for UvCheckerTexture {
+    #[allow(clippy::cast_possible_truncation)]
+    fn value(&self, u: f64, v: f64, p: &Point3) -> Color {
+        let column = (u * self.columns).floor() as i64;
+        let row = (v * self.rows).floor() as i64;
+
+        if (column + row) % 2 == 0 {
+            self.even.value(u, v, p)
+        } else {
+            self.odd.value(u, v, p)
+        }
+    }
+}
+
+/// Blends from `a` at `t = 0` to `b` at `t = 1`
+fn lerp(a: &Color, b: &Color, t: f64) -> Color {
+    (1. - t) * a + t * b
+}
+
+/// Marble with turbulent veins running across the x axis
+pub struct MarbleTexture {
+    noise: Perlin,
+    scale: f64,
+    base: Color,
+    vein: Color,
+}
+
+impl MarbleTexture {
+    #[must_use]
+    pub const fn new(noise: Perlin, scale: f64, base: Color, vein: Color) -> Self {
+        Self {
+            noise,
+            scale,
+            base,
+            vein,
+        }
+    }
+}
+
+impl Texture for MarbleTexture {
+    fn value(&self, _u: f64, _v: f64, p: &Point3) -> Color {
+        let p = self.scale * p;
+        let phase = 10_f64.mul_add(self.noise.turbulence(&p, 7), p.x());
+        // Thin dark veins where the sine wave peaks
+        let vein = (0.5 * (1. + phase.sin())).powi(8);
+        lerp(&self.base, &self.vein, vein)
+    }
+}
+
+/// Wood with growth rings around the y axis, warped by noise
+pub struct WoodTexture {
+    noise: Perlin,
+    rings_per_unit: f64,
+    light: Color,
+    dark: Color,
+}
+
+impl WoodTexture {
+    #[must_use]
+    pub const fn new(noise: Perlin, rings_per_unit: f64, light: Color, dark: Color) -> Self {
+        Self {
+            noise,
+            rings_per_unit,
+            light,
+            dark,
+        }
+    }
+}
+
+impl Texture for WoodTexture {
+    fn value(&self, _u: f64, _v: f64, p: &Point3) -> Color {
+        let radius = p.x().hypot(p.z()) * self.rings_per_unit;
+        let warp = 0.6 * self.noise.fbm(&(self.rings_per_unit * p), 4, 2., 0.5);
+        let ring = (radius + warp).rem_euclid(1.);
+        // Late wood at the end of each ring is darker, fading back into early wood
+        let dark = ring.powi(3);
+        let grain = 0.1_f64.mul_add(
+            self.noise
+                .noise(&Point3::new(40. * p.x(), p.y(), 40. * p.z())),
+            1.,
+        );
+        grain * lerp(&self.light, &self.dark, dark)
+    }
+}
+
+/// Irregular stones separated by mortar, from the cells of Worley noise
+pub struct StoneTexture {
+    cells: Worley,
+    noise: Perlin,
+    scale: f64,
+    mortar_width: f64,
+    stone: Color,
+    mortar: Color,
+}
+
+impl StoneTexture {
+    /// Stones about `1 / scale` units across, with mortar `mortar_width` of a stone wide
+    #[must_use]
+    pub const fn new(
+        cells: Worley,
+        noise: Perlin,
+        scale: f64,
+        mortar_width: f64,
+        stone: Color,
+        mortar: Color,
+    ) -> Self {
+        Self {
+            cells,
+            noise,
+            scale,
+            mortar_width,
+            stone,
+            mortar,
+        }
+    }
+}
+
+impl Texture for StoneTexture {
+    fn value(&self, _u: f64, _v: f64, p: &Point3) -> Color {
+        let p = self.scale * p;
+        let sample = self.cells.sample(&p);
+
+        // F2 - F1 is zero on the borders between cells and grows towards their centers
+        let edge = ((sample.f2 - sample.f1) / self.mortar_width).clamp(0., 1.);
+        let edge = edge * edge * 2_f64.mul_add(-edge, 3.);
+
+        let tint = sample.cell_value.mul_add(0.5, 0.75);
+        let grain = 0.15_f64.mul_add(self.noise.fbm(&(4. * &p), 4, 2., 0.5), 1.);
+        lerp(&self.mortar, &(tint * grain * &self.stone), edge)
+    }
+}
@@ -0,0 +1,208 @@
+use std::f64::consts::PI;
+
+use crate::aabb::Aabb;
+use crate::hittable::{HitRecord, Hittable};
+use crate::interval::Interval;
+use crate::material::Material;
+use crate::onb::Onb;
+use crate::polynomial::solve_quartic;
+use crate::ray::Ray;
+use crate::vec3::{Point3, Vec3};
+
+/// Torus around `center`, lying in the plane perpendicular to `axis`. The
+/// tube of radius `minor_radius` circles the axis at `major_radius`.
+pub struct Torus {
+    center: Point3,
+    onb: Onb, // `w` runs along the axis of symmetry
+    major_radius: f64,
+    minor_radius: f64,
+    mat: Material,
+    bbox: Aabb,
+}
+
+impl Torus {
+    #[must_use]
+    pub fn new(
+        center: Point3,
+        axis: &Vec3,
+        major_radius: f64,
+        minor_radius: f64,
+        mat: Material,
+    ) -> Self {
+        let onb = Onb::new(axis);
+
+        // Along each world axis, the tube's center circle extends by the major
+        // radius scaled by how far that axis is from the torus axis
+        let w = onb.w();
+        let extent = |n: f64| major_radius.mul_add(n.mul_add(-n, 1.).max(0.).sqrt(), minor_radius);
+        let half = Vec3::new(extent(w.x()), extent(w.y()), extent(w.z()));
+        let bbox = Aabb::from_points(&(&center - &half), &(&center + &half));
+
+        Self {
+            center,
+            onb,
+            major_radius,
+            minor_radius,
+            mat,
+            bbox,
+        }
+    }
+}
+
+impl Hittable for Torus {
+    #[allow(clippy::many_single_char_names)]
+    fn hit(&self, ray: &Ray, ray_t: &Interval) -> Option<HitRecord> {
+        // Solving the quartic is expensive, so reject rays that miss the box first
+        if !self.bbox.hit(ray, ray_t) {
+            return None;
+        }
+
+        let origin = self.onb.to_local(&(ray.origin() - &self.center));
+        let direction = self.onb.to_local(ray.direction());
+        let length = direction.length();
+        let dir = &direction / length;
+
+        // Start from the point on the ray closest to the center. This keeps the
+        // quartic's coefficients small and well conditioned for distant rays.
+        let t_closest = -origin.dot(&dir);
+        let start = &origin + &(t_closest * &dir);
+
+        // (|p|^2 + R^2 - r^2)^2 = 4 R^2 (x^2 + y^2), with p = start + s dir and |dir| = 1
+        let r_major_sq = self.major_radius.powi(2);
+        let od = start.dot(&dir);
+        let k = self
+            .minor_radius
+            .mul_add(-self.minor_radius, start.length_squared() + r_major_sq);
+        let dir_radial_sq = dir.x().mul_add(dir.x(), dir.y().powi(2));
+        let od_radial = start.x().mul_add(dir.x(), start.y() * dir.y());
+        let start_radial_sq = start.x().mul_add(start.x(), start.y().powi(2));
+
+        let roots = solve_quartic(
+            1.,
+            4. * od,
+            (4. * r_major_sq).mul_add(-dir_radial_sq, (4. * od).mul_add(od, 2. * k)),
+            (4. * od).mul_add(k, -8. * r_major_sq * od_radial),
+            k.mul_add(k, -4. * r_major_sq * start_radial_sq),
+        );
+        let t = roots
+            .into_iter()
+            .map(|s| (t_closest + s) / length)
+            .find(|&t| ray_t.surrounds(t))?;
+
+        // The outward normal points away from the nearest point on the tube's center circle
+        let p = &origin + &(t * &direction);
+        let radial = p.x().hypot(p.y());
+        let (ring_x, ring_y) = if radial > 0. {
+            (
+                p.x() * self.major_radius / radial,
+                p.y() * self.major_radius / radial,
+            )
+        } else {
+            (self.major_radius, 0.)
+        };
+        let local_normal = Vec3::new(p.x() - ring_x, p.y() - ring_y, p.z()).unit();
+
+        let u = (p.y().atan2(p.x()) + PI) / (2. * PI);
+        let v = (p.z().atan2(radial - self.major_radius) + PI) / (2. * PI);
+
+        let mut hit_record = HitRecord::new(ray.at(t), Vec3::new(0., 0., 0.), self.mat.clone(), t);
+        hit_record.set_face_normal(ray, self.onb.transform(&local_normal));
+        hit_record.set_uv(u, v);
+
+        Some(hit_record)
+    }
+
+    fn bounding_box(&self) -> &Aabb {
+        &self.bbox
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use super::*;
+    use crate::texture::SolidColor;
+    use crate::vec3::Color;
+
+    /// Every crossing of the ray with a torus around the z axis at the origin
+    fn crossings(origin: Point3, direction: Vec3) -> Vec<f64> {
+        let torus = Torus::new(
+            Point3::new(0., 0., 0.),
+            &Vec3::new(0., 0., 1.),
+            1.,
+            0.25,
+            Material::Lambertian {
+                albedo: Arc::new(SolidColor::new(Color::new(0.5, 0.5, 0.5))),
+            },
+        );
+        torus
+            .hit_all(
+                &Ray::new(origin, direction, 0.),
+                &Interval::new(0., f64::INFINITY),
+            )
+            .iter()
+            .map(HitRecord::t)
+            .collect()
+    }
+
+    fn assert_crossings(actual: &[f64], expected: &[f64]) {
+        assert_eq!(
+            actual.len(),
+            expected.len(),
+            "found crossings {actual:?}, expected {expected:?}"
+        );
+        for (t, expected_t) in actual.iter().zip(expected) {
+            assert!(
+                (t - expected_t).abs() <= 1e-9,
+                "found crossings {actual:?}, expected {expected:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn crosses_both_sides_of_the_tube() {
+        // Through the middle of the torus: in and out of the tube on both sides
+        assert_crossings(
+            &crossings(Point3::new(-5., 0., 0.), Vec3::new(1., 0., 0.)),
+            &[3.75, 4.25, 5.75, 6.25],
+        );
+
+        // Above the middle plane, where the tube is 2 * 0.15 wide
+        assert_crossings(
+            &crossings(Point3::new(-5., 0., 0.2), Vec3::new(1., 0., 0.)),
+            &[3.85, 4.15, 5.85, 6.15],
+        );
+    }
+
+    #[test]
+    fn misses_through_the_hole_and_over_the_top() {
+        // Along the axis of symmetry through the hole, and down through the tube
+        assert_crossings(
+            &crossings(Point3::new(0., 0., 5.), Vec3::new(0., 0., -1.)),
+            &[],
+        );
+        assert_crossings(
+            &crossings(Point3::new(0., 1., 5.), Vec3::new(0., 0., -1.)),
+            &[4.75, 5.25],
+        );
+
+        // Passing over the top of the tube
+        assert_crossings(
+            &crossings(Point3::new(-5., 0., 0.3), Vec3::new(1., 0., 0.)),
+            &[],
+        );
+    }
+
+    #[test]
+    fn far_rays_with_long_directions() {
+        assert_crossings(
+            &crossings(Point3::new(-500., 0., 0.2), Vec3::new(2., 0., 0.)),
+            &[249.425, 249.575, 250.425, 250.575],
+        );
+        assert_crossings(
+            &crossings(Point3::new(-2e6, 0., 0.), Vec3::new(1., 0., 0.)),
+            &[2e6 - 1.25, 2e6 - 0.75, 2e6 + 0.75, 2e6 + 1.25],
+        );
+    }
+}
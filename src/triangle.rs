@@ -0,0 +1,277 @@
+use rand::Rng;
+
+use crate::aabb::Aabb;
+use crate::hittable::{HitRecord, Hittable, area_to_solid_angle_pdf};
+use crate::interval::Interval;
+use crate::material::Material;
+use crate::ray::Ray;
+use crate::vec3::{Point3, Vec3};
+
+pub struct Triangle {
+    vertices: [Point3; 3],
+    normals: Option<[Vec3; 3]>, // Per-vertex shading normals
+    mat: Material,
+    bbox: Aabb,
+}
+
+impl Triangle {
+    #[must_use]
+    pub fn new(a: Point3, b: Point3, c: Point3, mat: Material) -> Self {
+        let bbox = Aabb::union(&Aabb::from_points(&a, &b), &Aabb::from_points(&b, &c));
+        Self {
+            vertices: [a, b, c],
+            normals: None,
+            mat,
+            bbox,
+        }
+    }
+
+    /// Use per-vertex normals, interpolated across the face, for shading
+    #[must_use]
+    pub const fn with_normals(mut self, na: Vec3, nb: Vec3, nc: Vec3) -> Self {
+        self.normals = Some([na, nb, nc]);
+        self
+    }
+}
+
+impl Hittable for Triangle {
+    fn hit(&self, ray: &Ray, ray_t: &Interval) -> Option<HitRecord> {
+        let [a, b, c] = &self.vertices;
+        let (t, b0, b1, b2) = intersect_watertight(ray, ray_t, a, b, c)?;
+
+        let shading_normal = self
+            .normals
+            .as_ref()
+            .map(|[na, nb, nc]| b0 * na + b1 * nb + b2 * nc);
+
+        Some(triangle_hit_record(
+            ray,
+            t,
+            [a, b, c],
+            (b1, b2),
+            shading_normal,
+            None,
+            self.mat.clone(),
+        ))
+    }
+
+    fn bounding_box(&self) -> &Aabb {
+        &self.bbox
+    }
+
+    fn occluded(&self, ray: &Ray, ray_t: &Interval) -> bool {
+        let [a, b, c] = &self.vertices;
+        intersect_watertight(ray, ray_t, a, b, c).is_some()
+    }
+
+    fn sample(&self, origin: &Point3, time: f64) -> Option<(Vec3, f64)> {
+        let [a, b, c] = &self.vertices;
+        let direction = sample_triangle(a, b, c) - origin;
+        let pdf = self.pdf(origin, &direction, time);
+        (pdf > 0.).then_some((direction, pdf))
+    }
+
+    fn pdf(&self, origin: &Point3, direction: &Vec3, time: f64) -> f64 {
+        let [a, b, c] = &self.vertices;
+        let area = 0.5 * (b - a).cross(&(c - a)).length();
+        let ray = Ray::new(origin.clone(), direction.clone(), time);
+        self.hit(&ray, &Interval::new(0.001, f64::INFINITY))
+            .map_or(0., |hit_record| {
+                area_to_solid_angle_pdf(&ray, &hit_record, area)
+            })
+    }
+}
+
+/// Uniformly random point on the triangle `v0`, `v1`, `v2`
+pub(crate) fn sample_triangle(v0: &Point3, v1: &Point3, v2: &Point3) -> Point3 {
+    let mut rng = rand::thread_rng();
+    // Folding the unit square onto the triangle with a square root keeps the density uniform
+    let s = rng.gen::<f64>().sqrt();
+    let (b1, b2) = (s * rng.gen::<f64>(), 1. - s);
+    ((1. - b1 - b2) * v0) + (b1 * v1) + (b2 * v2)
+}
+
+/// Builds the hit record shared by standalone and mesh triangles, with the
+/// geometric normal taken from the winding order `v0`, `v1`, `v2`. Without
+/// texture coordinates, the barycentric coordinates double as the hit's UVs.
+pub(crate) fn triangle_hit_record(
+    ray: &Ray,
+    t: f64,
+    [v0, v1, v2]: [&Point3; 3],
+    (b1, b2): (f64, f64),
+    shading_normal: Option<Vec3>,
+    uv: Option<(f64, f64)>,
+    mat: Material,
+) -> HitRecord {
+    let p = (1. - b1 - b2) * v0 + b1 * v1 + b2 * v2;
+    let outward_normal = (v1 - v0).cross(&(v2 - v0)).unit();
+
+    let mut hit_record = HitRecord::new(p, Vec3::new(0., 0., 0.), mat, t);
+    hit_record.set_face_normal(ray, outward_normal);
+    hit_record.set_barycentric(b1, b2);
+    let (u, v) = uv.unwrap_or((b1, b2));
+    hit_record.set_uv(u, v);
+    if let Some(n) = shading_normal {
+        if !n.near_zero() {
+            hit_record.set_shading_normal(n.unit());
+        }
+    }
+
+    hit_record
+}
+
+/// Watertight ray/triangle intersection (Woop, Benthin and Wald, 2013).
+///
+/// The triangle is projected into a space where the ray runs along +z from the
+/// origin, so the edge tests are evaluated the same way for every triangle that
+/// shares an edge and rays cannot slip between neighbours. Returns the ray
+/// parameter and the barycentric weights of `v0`, `v1` and `v2`.
+pub(crate) fn intersect_watertight(
+    ray: &Ray,
+    ray_t: &Interval,
+    v0: &Point3,
+    v1: &Point3,
+    v2: &Point3,
+) -> Option<(f64, f64, f64, f64)> {
+    let dir = ray.direction();
+
+    // Permute axes so that z is the dimension where the ray direction is largest
+    let kz = (0..3)
+        .max_by(|&i, &j| dir[i].abs().total_cmp(&dir[j].abs()))
+        .unwrap_or(2);
+    let mut kx = (kz + 1) % 3;
+    let mut ky = (kx + 1) % 3;
+    if dir[kz] < 0. {
+        std::mem::swap(&mut kx, &mut ky);
+    }
+
+    // Shear constants that align the ray direction with +z
+    let sx = dir[kx] / dir[kz];
+    let sy = dir[ky] / dir[kz];
+    let sz = 1. / dir[kz];
+
+    // Translate vertices relative to the ray origin
+    let p0 = v0 - ray.origin();
+    let p1 = v1 - ray.origin();
+    let p2 = v2 - ray.origin();
+
+    let p0x = sx.mul_add(-p0[kz], p0[kx]);
+    let p0y = sy.mul_add(-p0[kz], p0[ky]);
+    let p1x = sx.mul_add(-p1[kz], p1[kx]);
+    let p1y = sy.mul_add(-p1[kz], p1[ky]);
+    let p2x = sx.mul_add(-p2[kz], p2[kx]);
+    let p2y = sy.mul_add(-p2[kz], p2[ky]);
+
+    // Scaled barycentric coordinates from the 2D edge functions. These must not
+    // be fused: a shared edge has to give exactly the negated value when its
+    // vertices are swapped, which plain products and a subtraction guarantee.
+    #[allow(clippy::suboptimal_flops)]
+    let (e0, e1, e2) = (
+        p2x * p1y - p2y * p1x,
+        p0x * p2y - p0y * p2x,
+        p1x * p0y - p1y * p0x,
+    );
+
+    if (e0 < 0. || e1 < 0. || e2 < 0.) && (e0 > 0. || e1 > 0. || e2 > 0.) {
+        return None;
+    }
+
+    let det = e0 + e1 + e2;
+    if det == 0. {
+        return None;
+    }
+
+    let p0z = sz * p0[kz];
+    let p1z = sz * p1[kz];
+    let p2z = sz * p2[kz];
+    let t_scaled = e0.mul_add(p0z, e1.mul_add(p1z, e2 * p2z));
+    let t = t_scaled / det;
+    if !ray_t.surrounds(t) {
+        return None;
+    }
+
+    Some((t, e0 / det, e1 / det, e2 / det))
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::SeedableRng;
+    use rand::rngs::StdRng;
+
+    use super::*;
+
+    /// Vertices of a gently bumpy `n` by `n` grid over [0, 1] x [0, 1], and the
+    /// two triangles of each cell, alternating the diagonal they share. The
+    /// bumps are too shallow for the rays below to graze, so no ray can pass
+    /// between the triangles without a gap in the mesh.
+    #[allow(clippy::cast_precision_loss, clippy::many_single_char_names)]
+    fn grid(n: usize) -> (Vec<Point3>, Vec<[usize; 3]>) {
+        let mut vertices = Vec::new();
+        for j in 0..=n {
+            for i in 0..=n {
+                let (x, y) = (i as f64 / n as f64, j as f64 / n as f64);
+                let z = 0.02 * (7. * x).sin() * (5. * y).cos();
+                vertices.push(Point3::new(x, y, z));
+            }
+        }
+
+        let mut faces = Vec::new();
+        for j in 0..n {
+            for i in 0..n {
+                let corner = |di: usize, dj: usize| (j + dj) * (n + 1) + i + di;
+                let (a, b, c, d) = (corner(0, 0), corner(1, 0), corner(1, 1), corner(0, 1));
+                if (i + j) % 2 == 0 {
+                    faces.extend([[a, b, c], [a, c, d]]);
+                } else {
+                    faces.extend([[a, b, d], [b, c, d]]);
+                }
+            }
+        }
+        (vertices, faces)
+    }
+
+    #[test]
+    fn rays_through_shared_edges_and_vertices_hit() {
+        let (vertices, faces) = grid(8);
+        let ray_t = Interval::new(0., f64::INFINITY);
+        let hits = |ray: &Ray| {
+            faces
+                .iter()
+                .filter(|[a, b, c]| {
+                    intersect_watertight(ray, &ray_t, &vertices[*a], &vertices[*b], &vertices[*c])
+                        .is_some()
+                })
+                .count()
+        };
+
+        // Every edge a triangle shares with a neighbour, and every vertex
+        let mut targets: Vec<(Point3, Point3)> = Vec::new();
+        for [a, b, c] in &faces {
+            for (from, to) in [(a, b), (b, c), (c, a)] {
+                targets.push((vertices[*from].clone(), vertices[*to].clone()));
+            }
+        }
+
+        let mut rng = StdRng::seed_from_u64(3);
+        for (from, to) in &targets {
+            for fraction in [0., 0.5, rng.gen(), rng.gen()] {
+                let target = from + &(fraction * &(to - from));
+                // Skip the outer boundary of the grid, which only one triangle touches
+                let inside = |v: f64| v > 1e-9 && v < 1. - 1e-9;
+                if !(inside(target.x()) && inside(target.y())) {
+                    continue;
+                }
+
+                for _ in 0..8 {
+                    let origin = Point3::new(
+                        rng.gen_range(-1. ..2.),
+                        rng.gen_range(-1. ..2.),
+                        rng.gen_range(1. ..3.),
+                    );
+                    let ray = Ray::new(origin.clone(), &target - &origin, 0.);
+                    assert!(hits(&ray) > 0, "ray from {origin} slipped through {target}");
+                }
+            }
+        }
+    }
+}
@@ -5,8 +5,7 @@ use std::ops::{
 
 use rand::{Rng, thread_rng};
 
-use crate::color::linear_to_gamma;
-use crate::interval::Interval;
+use crate::color::{linear_to_gamma, to_byte};
 
 #[derive(Clone)]
 pub struct Vec3 {
@@ -17,14 +16,17 @@ pub type Point3 = Vec3;
 pub type Color = Vec3;
 
 impl Vec3 {
+    #[must_use]
     pub const fn new(e0: f64, e1: f64, e2: f64) -> Self {
         Self { e: [e0, e1, e2] }
     }
 
+    #[must_use]
     pub const fn default() -> Self {
         Self { e: [0., 0., 0.] }
     }
 
+    #[must_use]
     pub fn random() -> Self {
         let mut rng = rand::thread_rng();
         Self {
@@ -32,6 +34,7 @@ impl Vec3 {
         }
     }
 
+    #[must_use]
     pub fn random_in_interval(min: f64, max: f64) -> Self {
         let mut rng = rand::thread_rng();
         Self {
@@ -43,6 +46,7 @@ impl Vec3 {
         }
     }
 
+    #[must_use]
     pub fn random_in_unit_sphere() -> Self {
         loop {
             let p = Self::random_in_interval(-1., 1.);
@@ -52,10 +56,12 @@ impl Vec3 {
         }
     }
 
+    #[must_use]
     pub fn random_unit_vector() -> Self {
         Self::random_in_unit_sphere().unit()
     }
 
+    #[must_use]
     pub fn random_on_hemisphere(normal: &Self) -> Self {
         let on_unit_sphere = Self::random_unit_vector();
         // Check if in the same hemisphere as the normal
@@ -66,6 +72,7 @@ impl Vec3 {
         }
     }
 
+    #[must_use]
     pub fn random_in_unit_disk() -> Self {
         let mut rng = thread_rng();
         loop {
@@ -78,30 +85,37 @@ impl Vec3 {
         }
     }
 
+    #[must_use]
     pub fn x(&self) -> f64 {
         self[0]
     }
 
+    #[must_use]
     pub fn y(&self) -> f64 {
         self[1]
     }
 
+    #[must_use]
     pub fn z(&self) -> f64 {
         self[2]
     }
 
+    #[must_use]
     pub fn dot(&self, other: &Self) -> f64 {
         self.e.iter().zip(other.e).map(|(u, v)| u * v).sum()
     }
 
+    #[must_use]
     pub fn length_squared(&self) -> f64 {
         self.dot(self)
     }
 
+    #[must_use]
     pub fn length(&self) -> f64 {
         self.length_squared().sqrt()
     }
 
+    #[must_use]
     pub fn cross(&self, other: &Self) -> Self {
         Self {
             e: [
@@ -112,19 +126,23 @@ impl Vec3 {
         }
     }
 
+    #[must_use]
     pub fn unit(&self) -> Self {
         self / self.length()
     }
 
     /// Returns true if the vector is close to zero in all dimensions
+    #[must_use]
     pub fn near_zero(&self) -> bool {
         self.e.iter().all(|v| v.abs() < 1e-8)
     }
 
+    #[must_use]
     pub fn reflect(&self, n: &Self) -> Self {
         self - n * 2. * self.dot(n)
     }
 
+    #[must_use]
     pub fn refract(&self, n: &Self, etai_over_etat: f64) -> Self {
         let cos_theta = self.neg().dot(n).min(1.);
         let r_out_perp = etai_over_etat * (self + cos_theta * n);
@@ -157,6 +175,16 @@ impl Add for Vec3 {
     }
 }
 
+impl Add<&Self> for Vec3 {
+    type Output = Self;
+
+    fn add(self, rhs: &Self) -> Self::Output {
+        Self {
+            e: [self[0] + rhs[0], self[1] + rhs[1], self[2] + rhs[2]],
+        }
+    }
+}
+
 impl Add<Vec3> for &Vec3 {
     type Output = Vec3;
 
@@ -367,21 +395,11 @@ impl Display for Vec3 {
 
 impl From<Vec3> for image::Rgb<u8> {
     fn from(value: Vec3) -> Self {
-        let mut r = value.x();
-        let mut g = value.y();
-        let mut b = value.z();
-
         // Apply a linear to gamma transform for gamma 2
-        r = linear_to_gamma(r);
-        g = linear_to_gamma(g);
-        b = linear_to_gamma(b);
-
-        let intensity = Interval::new(0.000, 0.999);
-        #[allow(clippy::cast_sign_loss, clippy::cast_possible_truncation)]
         Self([
-            (256. * intensity.clamp(r)) as u8,
-            (256. * intensity.clamp(g)) as u8,
-            (256. * intensity.clamp(b)) as u8,
+            to_byte(linear_to_gamma(value.x())),
+            to_byte(linear_to_gamma(value.y())),
+            to_byte(linear_to_gamma(value.z())),
         ])
     }
 }